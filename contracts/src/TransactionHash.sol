@@ -0,0 +1,139 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/// @notice Canonical transaction encoding and hash. Byte-for-byte identical to
+/// `PublicTransaction::canonical_bytes` / `txid` in cryptography-types.
+/// Integers are big-endian and every list is prefixed with a uint32 length.
+library TransactionHash {
+    bytes8 internal constant MAGIC = "GELAP_TX";
+    uint8 internal constant VERSION = 1;
+
+    struct Input {
+        bytes32 commitment;
+        bytes32 keyImage;
+        bytes32[] ring;
+    }
+
+    struct Output {
+        bytes32 commitment;
+        address stealthAddress;
+        bytes ephemeralPubkey;
+    }
+
+    struct Note {
+        bytes32 ringPubkey;
+        bytes8 encryptedAmount;
+        bytes32 encryptedBlinding;
+    }
+
+    struct Withdrawal {
+        address recipient;
+        uint64 amount;
+    }
+
+    struct Transaction {
+        Input[] inputs;
+        Output[] outputs;
+        Note[] notes;
+        Withdrawal[] withdrawals;
+        uint64 fee;
+        bool hasRelayer;
+        address relayer;
+    }
+
+    /// Size of `encode(txn)` in bytes.
+    function encodedLength(Transaction memory txn) internal pure returns (uint256 len) {
+        len = 8 + 1 + 4;
+        for (uint256 i = 0; i < txn.inputs.length; i++) {
+            len += 32 + 32 + 4 + 32 * txn.inputs[i].ring.length;
+        }
+        len += 4;
+        for (uint256 i = 0; i < txn.outputs.length; i++) {
+            len += 32 + 20 + 4 + txn.outputs[i].ephemeralPubkey.length;
+        }
+        len += 4 + 72 * txn.notes.length;
+        len += 4 + 28 * txn.withdrawals.length;
+        len += 8 + 1 + 20;
+    }
+
+    /// Writes into one buffer sized up front; appending with
+    /// `abi.encodePacked(out, ...)` would copy it on every field.
+    function encode(Transaction memory txn) internal pure returns (bytes memory out) {
+        uint256 len = encodedLength(txn);
+        // Every write stores a full left-aligned word, so keep one spare word
+        out = new bytes(len + 32);
+        assembly {
+            mstore(out, len)
+        }
+
+        uint256 at = _put(out, 0, MAGIC, 8);
+        at = _put(out, at, bytes32(uint256(VERSION) << 248), 1);
+
+        at = _putLength(out, at, txn.inputs.length);
+        for (uint256 i = 0; i < txn.inputs.length; i++) {
+            Input memory input = txn.inputs[i];
+            at = _put(out, at, input.commitment, 32);
+            at = _put(out, at, input.keyImage, 32);
+            at = _putLength(out, at, input.ring.length);
+            for (uint256 j = 0; j < input.ring.length; j++) {
+                at = _put(out, at, input.ring[j], 32);
+            }
+        }
+
+        at = _putLength(out, at, txn.outputs.length);
+        for (uint256 i = 0; i < txn.outputs.length; i++) {
+            Output memory output = txn.outputs[i];
+            at = _put(out, at, output.commitment, 32);
+            at = _put(out, at, bytes32(bytes20(output.stealthAddress)), 20);
+            at = _putLength(out, at, output.ephemeralPubkey.length);
+            at = _putBytes(out, at, output.ephemeralPubkey);
+        }
+
+        at = _putLength(out, at, txn.notes.length);
+        for (uint256 i = 0; i < txn.notes.length; i++) {
+            Note memory note = txn.notes[i];
+            at = _put(out, at, note.ringPubkey, 32);
+            at = _put(out, at, note.encryptedAmount, 8);
+            at = _put(out, at, note.encryptedBlinding, 32);
+        }
+
+        at = _putLength(out, at, txn.withdrawals.length);
+        for (uint256 i = 0; i < txn.withdrawals.length; i++) {
+            at = _put(out, at, bytes32(bytes20(txn.withdrawals[i].recipient)), 20);
+            at = _put(out, at, bytes32(uint256(txn.withdrawals[i].amount) << 192), 8);
+        }
+
+        at = _put(out, at, bytes32(uint256(txn.fee) << 192), 8);
+        at = _put(out, at, txn.hasRelayer ? bytes32(uint256(1) << 248) : bytes32(0), 1);
+        at = _put(out, at, bytes32(bytes20(txn.hasRelayer ? txn.relayer : address(0))), 20);
+        assert(at == len);
+    }
+
+    function hash(Transaction memory txn) internal pure returns (bytes32) {
+        return keccak256(encode(txn));
+    }
+
+    /// Stores `word` at `at` and advances by `size`; the bytes past `size` are
+    /// overwritten by the next field or land in the spare word.
+    function _put(bytes memory out, uint256 at, bytes32 word, uint256 size) private pure returns (uint256) {
+        assembly {
+            mstore(add(add(out, 32), at), word)
+        }
+        return at + size;
+    }
+
+    function _putLength(bytes memory out, uint256 at, uint256 length) private pure returns (uint256) {
+        return _put(out, at, bytes32(uint256(uint32(length)) << 224), 4);
+    }
+
+    function _putBytes(bytes memory out, uint256 at, bytes memory data) private pure returns (uint256) {
+        for (uint256 k = 0; k < data.length; k += 32) {
+            bytes32 word;
+            assembly {
+                word := mload(add(add(data, 32), k))
+            }
+            _put(out, at + k, word, 32);
+        }
+        return at + data.length;
+    }
+}
@@ -0,0 +1,48 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import {Test} from "forge-std/Test.sol";
+import {TransactionHash} from "../src/TransactionHash.sol";
+
+contract TransactionHashTest is Test {
+    function fill(bytes1 b) internal pure returns (bytes32) {
+        return bytes32(uint256(uint8(b)) * 0x0101010101010101010101010101010101010101010101010101010101010101);
+    }
+
+    // Same vector as `test_txid_vector` in types/src/transaction.rs
+    function testMatchesRustVector() public {
+        TransactionHash.Transaction memory txn;
+
+        bytes32[] memory ring = new bytes32[](2);
+        ring[0] = fill(0x44);
+        ring[1] = fill(0x55);
+        txn.inputs = new TransactionHash.Input[](1);
+        txn.inputs[0] = TransactionHash.Input(fill(0x11), fill(0x33), ring);
+
+        bytes memory ephemeralPubkey = new bytes(33);
+        for (uint256 i = 0; i < 33; i++) {
+            ephemeralPubkey[i] = 0x02;
+        }
+        txn.outputs = new TransactionHash.Output[](1);
+        txn.outputs[0] = TransactionHash.Output(
+            fill(0x22), address(bytes20(fill(0x66))), ephemeralPubkey
+        );
+
+        txn.notes = new TransactionHash.Note[](1);
+        txn.notes[0] = TransactionHash.Note(fill(0x77), bytes8(fill(0x88)), fill(0x99));
+
+        txn.withdrawals = new TransactionHash.Withdrawal[](1);
+        txn.withdrawals[0] = TransactionHash.Withdrawal(address(bytes20(fill(0xaa))), 1000);
+
+        txn.fee = 25;
+        txn.hasRelayer = true;
+        txn.relayer = address(bytes20(fill(0xbb)));
+
+        assertEq(TransactionHash.encode(txn).length, 375);
+        assertEq(TransactionHash.encodedLength(txn), 375);
+        assertEq(
+            TransactionHash.hash(txn),
+            bytes32(0x5bda5303dcda1895593f984fbc70d1b0cae4f8a3b2c97808c766d3650a12c177)
+        );
+    }
+}
@@ -0,0 +1,439 @@
+// Picks which owned outputs fund a spend.
+//
+// Every input costs `fee_per_input` and links the outputs spent together, so
+// the input count is capped twice: by what the guest can prove (MAX_INPUTS)
+// and by an optional, stricter consolidation limit chosen for privacy.
+
+use crate::errors::{CryptoError, Result};
+use crate::keys::StealthMetaAddress;
+use crate::transfer::TransferBuilder;
+use cryptography_types::{
+    errors::BalanceError,
+    transaction::{checked_sum, MAX_INPUTS},
+    wallet::OwnedOutput,
+};
+use std::cmp::Reverse;
+
+/// Search steps branch-and-bound may take before giving up on an exact match.
+const BNB_MAX_TRIES: usize = 100_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionStrategy {
+    /// Looks for inputs that cover the spend without a change output, paying
+    /// any excess up to the dust limit as fee. Falls back to largest-first.
+    BranchAndBound,
+    /// Spends the largest outputs first. Fewest inputs, usually with change.
+    LargestFirst,
+    /// Spends the smallest single output that covers the spend, so nothing is
+    /// linked. Falls back to largest-first when no single output is enough.
+    Privacy,
+}
+
+/// The chosen inputs. Their total is always `amount + fee + change`.
+#[derive(Debug)]
+pub struct Selection<'a> {
+    pub inputs: Vec<&'a OwnedOutput>,
+    pub fee: u64,
+    pub change: u64,
+}
+
+impl<'a> Selection<'a> {
+    /// Fails if the inputs sum past `u64::MAX`; [`CoinSelector::select`]
+    /// never returns such a selection.
+    pub fn total(&self) -> Result<u64> {
+        checked_sum(self.inputs.iter().map(|o| o.amount))
+            .ok_or(CryptoError::Balance(BalanceError::AmountOverflow))
+    }
+
+    /// Adds the inputs, the fee and, if there is any, the change output.
+    pub fn apply(
+        self,
+        mut builder: TransferBuilder<'a>,
+        change_to: StealthMetaAddress,
+    ) -> TransferBuilder<'a> {
+        for input in self.inputs {
+            builder = builder.add_input(input);
+        }
+        if self.change > 0 {
+            builder = builder.add_recipient(change_to, self.change);
+        }
+        builder.with_fee(self.fee)
+    }
+}
+
+/// Fee is `base_fee + fee_per_input * inputs`. Change at or below
+/// `dust_limit` is not worth an output and goes to the fee instead.
+#[derive(Debug, Clone)]
+pub struct CoinSelector {
+    base_fee: u64,
+    fee_per_input: u64,
+    dust_limit: u64,
+    max_inputs: usize,
+}
+
+impl Default for CoinSelector {
+    fn default() -> Self {
+        Self {
+            base_fee: 0,
+            fee_per_input: 0,
+            dust_limit: 0,
+            max_inputs: MAX_INPUTS,
+        }
+    }
+}
+
+impl CoinSelector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_base_fee(mut self, base_fee: u64) -> Self {
+        self.base_fee = base_fee;
+        self
+    }
+
+    pub fn with_fee_per_input(mut self, fee_per_input: u64) -> Self {
+        self.fee_per_input = fee_per_input;
+        self
+    }
+
+    pub fn with_dust_limit(mut self, dust_limit: u64) -> Self {
+        self.dust_limit = dust_limit;
+        self
+    }
+
+    /// Consolidation limit. Never above [`MAX_INPUTS`].
+    pub fn with_max_inputs(mut self, max_inputs: usize) -> Self {
+        self.max_inputs = max_inputs.min(MAX_INPUTS);
+        self
+    }
+
+    pub fn fee(&self, inputs: usize) -> u64 {
+        self.fee_per_input
+            .saturating_mul(inputs as u64)
+            .saturating_add(self.base_fee)
+    }
+
+    /// Selects unspent outputs paying `amount` to recipients. Outputs worth no
+    /// more than their own input fee are never selected.
+    pub fn select<'a, I>(
+        &self,
+        outputs: I,
+        amount: u64,
+        strategy: SelectionStrategy,
+    ) -> Result<Selection<'a>>
+    where
+        I: IntoIterator<Item = &'a OwnedOutput>,
+    {
+        let mut candidates: Vec<&OwnedOutput> = outputs
+            .into_iter()
+            .filter(|o| o.is_unspent() && o.amount > self.fee_per_input)
+            .collect();
+        candidates.sort_by_key(|o| Reverse(o.amount));
+
+        // Amount and base fee, to be covered by effective input values
+        let target = amount as u128 + self.base_fee as u128;
+
+        let picked = match strategy {
+            SelectionStrategy::BranchAndBound => self
+                .branch_and_bound(&candidates, target)
+                .map(Ok)
+                .unwrap_or_else(|| self.largest_first(&candidates, target)),
+            SelectionStrategy::LargestFirst => self.largest_first(&candidates, target),
+            SelectionStrategy::Privacy => candidates
+                .iter()
+                .rposition(|o| self.effective(o) >= target)
+                .map(|i| Ok(vec![i]))
+                .unwrap_or_else(|| self.largest_first(&candidates, target)),
+        }?;
+
+        let inputs: Vec<&OwnedOutput> = picked.iter().map(|&i| candidates[i]).collect();
+        let total: u128 = inputs.iter().map(|o| o.amount as u128).sum();
+        // The guest sums inputs in u64, so a larger total could never be spent
+        if total > u64::MAX as u128 {
+            return Err(BalanceError::AmountOverflow.into());
+        }
+        let mut fee = self.fee(inputs.len());
+        let mut change = (total - amount as u128 - fee as u128) as u64;
+        if change <= self.dust_limit {
+            fee += change;
+            change = 0;
+        }
+
+        Ok(Selection {
+            inputs,
+            fee,
+            change,
+        })
+    }
+
+    fn effective(&self, output: &OwnedOutput) -> u128 {
+        (output.amount - self.fee_per_input) as u128
+    }
+
+    fn largest_first(&self, candidates: &[&OwnedOutput], target: u128) -> Result<Vec<usize>> {
+        let mut sum = 0u128;
+        for (i, output) in candidates.iter().enumerate() {
+            sum += self.effective(output);
+            if sum >= target {
+                if i + 1 > self.max_inputs {
+                    return Err(CryptoError::TooManyInputs {
+                        count: i + 1,
+                        max: self.max_inputs,
+                    });
+                }
+                return Ok((0..=i).collect());
+            }
+        }
+
+        Err(CryptoError::InsufficientFunds {
+            needed: target.min(u64::MAX as u128) as u64,
+            available: sum.min(u64::MAX as u128) as u64,
+        })
+    }
+
+    /// Depth-first search over candidates sorted largest first for a subset
+    /// whose effective value lands in `[target, target + dust_limit]`.
+    fn branch_and_bound(&self, candidates: &[&OwnedOutput], target: u128) -> Option<Vec<usize>> {
+        let values: Vec<u128> = candidates.iter().map(|o| self.effective(o)).collect();
+        let search = Search {
+            values: &values,
+            target,
+            upper: target + self.dust_limit as u128,
+            max_inputs: self.max_inputs,
+        };
+
+        let mut selected = Vec::new();
+        let mut tries = BNB_MAX_TRIES;
+        let remaining = values.iter().sum();
+        search
+            .run(0, 0, remaining, &mut selected, &mut tries)
+            .then_some(selected)
+    }
+}
+
+struct Search<'a> {
+    values: &'a [u128],
+    target: u128,
+    upper: u128,
+    max_inputs: usize,
+}
+
+impl Search<'_> {
+    fn run(
+        &self,
+        index: usize,
+        sum: u128,
+        remaining: u128,
+        selected: &mut Vec<usize>,
+        tries: &mut usize,
+    ) -> bool {
+        if sum >= self.target {
+            return sum <= self.upper;
+        }
+        if index == self.values.len()
+            || selected.len() == self.max_inputs
+            || sum + remaining < self.target
+            || *tries == 0
+        {
+            return false;
+        }
+        *tries -= 1;
+
+        let value = self.values[index];
+        selected.push(index);
+        if self.run(index + 1, sum + value, remaining - value, selected, tries) {
+            return true;
+        }
+        selected.pop();
+
+        self.run(index + 1, sum, remaining - value, selected, tries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(amount: u64) -> OwnedOutput {
+        OwnedOutput {
+            commitment: [amount as u8; 32],
+            amount,
+            blinding: [0u8; 32],
+            stealth_address: [0u8; 20],
+            ring_pubkey: [0u8; 32],
+            ring_key_offset: [0u8; 32],
+            key_image: [amount as u8; 32],
+            height: Some(1),
+            spent: false,
+            spent_height: None,
+        }
+    }
+
+    fn outputs(amounts: &[u64]) -> Vec<OwnedOutput> {
+        amounts.iter().map(|&a| output(a)).collect()
+    }
+
+    fn amounts(selection: &Selection) -> Vec<u64> {
+        let mut amounts: Vec<u64> = selection.inputs.iter().map(|o| o.amount).collect();
+        amounts.sort();
+        amounts
+    }
+
+    fn assert_balanced(selection: &Selection, amount: u64) {
+        assert_eq!(
+            selection.total().unwrap(),
+            amount + selection.fee + selection.change
+        );
+    }
+
+    #[test]
+    fn test_branch_and_bound_exact_match() {
+        let pool = outputs(&[50, 30, 20, 10, 7]);
+        let selector = CoinSelector::new().with_fee_per_input(1);
+
+        // 30 + 10 = 40 = 38 + two input fees
+        let selection = selector
+            .select(&pool, 38, SelectionStrategy::BranchAndBound)
+            .unwrap();
+        assert_eq!(amounts(&selection), vec![10, 30]);
+        assert_eq!(selection.fee, 2);
+        assert_eq!(selection.change, 0);
+        assert_balanced(&selection, 38);
+    }
+
+    #[test]
+    fn test_branch_and_bound_dust_goes_to_fee() {
+        let pool = outputs(&[50, 33]);
+        let selector = CoinSelector::new().with_base_fee(2).with_dust_limit(3);
+
+        let selection = selector
+            .select(&pool, 29, SelectionStrategy::BranchAndBound)
+            .unwrap();
+        assert_eq!(amounts(&selection), vec![33]);
+        assert_eq!(selection.fee, 4);
+        assert_eq!(selection.change, 0);
+        assert_balanced(&selection, 29);
+    }
+
+    #[test]
+    fn test_branch_and_bound_falls_back() {
+        let pool = outputs(&[50, 30]);
+        let selection = CoinSelector::new()
+            .select(&pool, 45, SelectionStrategy::BranchAndBound)
+            .unwrap();
+        assert_eq!(amounts(&selection), vec![50]);
+        assert_eq!(selection.change, 5);
+    }
+
+    #[test]
+    fn test_largest_first() {
+        let pool = outputs(&[5, 40, 25, 10]);
+        let selector = CoinSelector::new().with_base_fee(3).with_fee_per_input(2);
+
+        let selection = selector
+            .select(&pool, 58, SelectionStrategy::LargestFirst)
+            .unwrap();
+        assert_eq!(amounts(&selection), vec![25, 40]);
+        assert_eq!(selection.fee, 7);
+        assert_eq!(selection.change, 0);
+
+        let selection = selector
+            .select(&pool, 50, SelectionStrategy::LargestFirst)
+            .unwrap();
+        assert_eq!(selection.change, 8);
+        assert_balanced(&selection, 50);
+    }
+
+    #[test]
+    fn test_privacy_prefers_single_output() {
+        let pool = outputs(&[100, 60, 45, 20, 20]);
+        let selector = CoinSelector::new().with_fee_per_input(1);
+
+        let selection = selector
+            .select(&pool, 40, SelectionStrategy::Privacy)
+            .unwrap();
+        assert_eq!(amounts(&selection), vec![45]);
+        assert_eq!(selection.change, 4);
+
+        let selection = selector
+            .select(&pool, 150, SelectionStrategy::Privacy)
+            .unwrap();
+        assert_eq!(amounts(&selection), vec![60, 100]);
+    }
+
+    #[test]
+    fn test_consolidation_limit() {
+        let pool = outputs(&[10; 20]);
+
+        let selection = CoinSelector::new()
+            .select(&pool, 150, SelectionStrategy::LargestFirst)
+            .unwrap();
+        assert_eq!(selection.inputs.len(), 15);
+
+        let err = CoinSelector::new()
+            .with_max_inputs(4)
+            .select(&pool, 50, SelectionStrategy::BranchAndBound)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            CryptoError::TooManyInputs { count: 5, max: 4 }
+        ));
+
+        // The guest limit always applies
+        let err = CoinSelector::new()
+            .with_max_inputs(100)
+            .select(&pool, 190, SelectionStrategy::LargestFirst)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            CryptoError::TooManyInputs {
+                count: 19,
+                max: MAX_INPUTS
+            }
+        ));
+    }
+
+    #[test]
+    fn test_insufficient_funds() {
+        let mut pool = outputs(&[30, 20, 1]);
+        pool[0].spent = true;
+        let selector = CoinSelector::new().with_fee_per_input(1);
+
+        // The spent 30 and the 1 that only pays its own fee are skipped
+        let err = selector
+            .select(&pool, 25, SelectionStrategy::LargestFirst)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            CryptoError::InsufficientFunds {
+                needed: 25,
+                available: 19
+            }
+        ));
+    }
+
+    #[test]
+    fn test_total_overflow() {
+        let pool = outputs(&[u64::MAX, 1]);
+        let selection = Selection {
+            inputs: pool.iter().collect(),
+            fee: 0,
+            change: 0,
+        };
+        assert!(matches!(
+            selection.total(),
+            Err(CryptoError::Balance(BalanceError::AmountOverflow))
+        ));
+
+        // Covering the amount takes both outputs, whose sum has no u64 total
+        let pool = outputs(&[u64::MAX - 10, 100]);
+        let err = CoinSelector::new()
+            .select(&pool, u64::MAX - 5, SelectionStrategy::LargestFirst)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            CryptoError::Balance(BalanceError::AmountOverflow)
+        ));
+    }
+}
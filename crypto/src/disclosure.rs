@@ -0,0 +1,264 @@
+// Proofs about what a single commitment C = a*G + r*H contains, for an
+// auditor or counterparty, that reveal neither the blinding factor nor
+// anything about other outputs:
+//
+// - `OpeningProof` discloses the amount: a Schnorr proof of knowledge of r
+//   such that C - a*G = r*H.
+// - `MinimumAmountProof` only shows the amount is at least X: a Bulletproofs
+//   range proof that C - X*G commits to a value in [0, 2^64).
+//
+// Both bind a verifier-chosen context, such as the auditor's request nonce,
+// so a proof cannot be replayed to someone else.
+
+use crate::errors::{CryptoError, Result};
+use crate::pedersen::{get_h_generator, PedersenCommitment};
+use crate::sigma::{SchnorrProof, SchnorrStatement};
+use bulletproofs::{BulletproofGens, PedersenGens, RangeProof};
+use curve25519_dalek::{constants::RISTRETTO_BASEPOINT_POINT, scalar::Scalar};
+use merlin::Transcript;
+use serde::{Deserialize, Serialize};
+
+/// Bit length of the range proved by [`MinimumAmountProof`].
+pub const RANGE_BITS: usize = 64;
+
+/// Discloses the amount of one commitment.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct OpeningProof {
+    pub amount: u64,
+    /// Knowledge of the blinding `r` with `C - amount*G = r*H`.
+    pub proof: SchnorrProof,
+}
+
+impl OpeningProof {
+    /// amount (8, little-endian) || Schnorr proof (64)
+    pub const SIZE: usize = 8 + SchnorrProof::SIZE;
+
+    pub fn prove(
+        commitment: &PedersenCommitment,
+        amount: u64,
+        blinding: &Scalar,
+        context: &[u8],
+    ) -> Result<Self> {
+        if !commitment.verify(amount, blinding) {
+            return Err(CryptoError::CommitmentVerificationFailed);
+        }
+
+        let proof = SchnorrProof::prove(
+            &mut opening_transcript(commitment, amount, context),
+            &opening_statement(commitment, amount),
+            blinding,
+        );
+
+        Ok(Self { amount, proof })
+    }
+
+    pub fn verify(&self, commitment: &PedersenCommitment, context: &[u8]) -> Result<()> {
+        self.proof.verify(
+            &mut opening_transcript(commitment, self.amount, context),
+            &opening_statement(commitment, self.amount),
+        )
+    }
+
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0u8; Self::SIZE];
+        bytes[..8].copy_from_slice(&self.amount.to_le_bytes());
+        bytes[8..].copy_from_slice(&self.proof.to_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != Self::SIZE {
+            return Err(CryptoError::Deserialization(format!(
+                "Opening proof must be {} bytes, got {}",
+                Self::SIZE,
+                bytes.len()
+            )));
+        }
+
+        Ok(Self {
+            amount: u64::from_le_bytes(bytes[..8].try_into().expect("8 bytes")),
+            proof: SchnorrProof::from_bytes(&bytes[8..])?,
+        })
+    }
+}
+
+/// Shows a commitment holds at least `minimum` without disclosing the amount.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MinimumAmountProof {
+    pub minimum: u64,
+    #[serde(with = "crate::serde_hex::range_proof")]
+    pub range_proof: RangeProof,
+}
+
+impl MinimumAmountProof {
+    pub fn prove(
+        commitment: &PedersenCommitment,
+        amount: u64,
+        blinding: &Scalar,
+        minimum: u64,
+        context: &[u8],
+    ) -> Result<Self> {
+        if !commitment.verify(amount, blinding) {
+            return Err(CryptoError::CommitmentVerificationFailed);
+        }
+        let excess = amount.checked_sub(minimum).ok_or_else(|| {
+            CryptoError::InvalidInput(format!("Amount is below the minimum of {}", minimum))
+        })?;
+
+        let mut transcript = minimum_transcript(commitment, minimum, context);
+        let (range_proof, _) = RangeProof::prove_single(
+            &BulletproofGens::new(RANGE_BITS, 1),
+            &pedersen_gens(),
+            &mut transcript,
+            excess,
+            blinding,
+            RANGE_BITS,
+        )
+        .map_err(|e| CryptoError::RangeProof(e.to_string()))?;
+
+        Ok(Self {
+            minimum,
+            range_proof,
+        })
+    }
+
+    pub fn verify(&self, commitment: &PedersenCommitment, context: &[u8]) -> Result<()> {
+        let excess = commitment.point - Scalar::from(self.minimum) * RISTRETTO_BASEPOINT_POINT;
+
+        let mut transcript = minimum_transcript(commitment, self.minimum, context);
+        self.range_proof
+            .verify_single(
+                &BulletproofGens::new(RANGE_BITS, 1),
+                &pedersen_gens(),
+                &mut transcript,
+                &excess.compress(),
+                RANGE_BITS,
+            )
+            .map_err(|_| CryptoError::ProofVerificationFailed)
+    }
+
+    /// minimum (8, little-endian) || range proof
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.minimum.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&self.range_proof.to_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < 8 {
+            return Err(CryptoError::Deserialization(
+                "Minimum amount proof is too short".into(),
+            ));
+        }
+
+        Ok(Self {
+            minimum: u64::from_le_bytes(bytes[..8].try_into().expect("8 bytes")),
+            range_proof: RangeProof::from_bytes(&bytes[8..])
+                .map_err(|e| CryptoError::Deserialization(e.to_string()))?,
+        })
+    }
+}
+
+/// The generators of [`PedersenCommitment`], in the form Bulletproofs takes.
+pub fn pedersen_gens() -> PedersenGens {
+    PedersenGens {
+        B: RISTRETTO_BASEPOINT_POINT,
+        B_blinding: get_h_generator(),
+    }
+}
+
+/// `C - amount*G = r*H`
+fn opening_statement(commitment: &PedersenCommitment, amount: u64) -> SchnorrStatement {
+    SchnorrStatement {
+        base: get_h_generator(),
+        public: commitment.point - Scalar::from(amount) * RISTRETTO_BASEPOINT_POINT,
+    }
+}
+
+fn opening_transcript(commitment: &PedersenCommitment, amount: u64, context: &[u8]) -> Transcript {
+    let mut transcript = Transcript::new(b"GELAP_OPENING_PROOF_V1");
+    transcript.append_message(b"context", context);
+    transcript.append_message(b"commitment", &commitment.to_bytes());
+    transcript.append_u64(b"amount", amount);
+    transcript
+}
+
+fn minimum_transcript(commitment: &PedersenCommitment, minimum: u64, context: &[u8]) -> Transcript {
+    let mut transcript = Transcript::new(b"GELAP_MINIMUM_AMOUNT_PROOF_V1");
+    transcript.append_message(b"context", context);
+    transcript.append_message(b"commitment", &commitment.to_bytes());
+    transcript.append_u64(b"minimum", minimum);
+    transcript
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pedersen::generate_blinding;
+
+    fn commitment(amount: u64) -> (PedersenCommitment, Scalar) {
+        let blinding = generate_blinding();
+        (PedersenCommitment::new(amount, &blinding), blinding)
+    }
+
+    #[test]
+    fn test_opening_proof() {
+        let (c, blinding) = commitment(1_000);
+        let proof = OpeningProof::prove(&c, 1_000, &blinding, b"audit-42").unwrap();
+
+        assert!(proof.verify(&c, b"audit-42").is_ok());
+        assert!(proof.verify(&c, b"audit-43").is_err());
+
+        let (other, _) = commitment(1_000);
+        assert!(proof.verify(&other, b"audit-42").is_err());
+
+        let mut lied = proof;
+        lied.amount = 1_001;
+        assert!(lied.verify(&c, b"audit-42").is_err());
+
+        assert!(OpeningProof::prove(&c, 999, &blinding, b"audit-42").is_err());
+    }
+
+    #[test]
+    fn test_opening_proof_bytes() {
+        let (c, blinding) = commitment(7);
+        let proof = OpeningProof::prove(&c, 7, &blinding, b"").unwrap();
+
+        let decoded = OpeningProof::from_bytes(&proof.to_bytes()).unwrap();
+        assert_eq!(decoded, proof);
+        assert!(decoded.verify(&c, b"").is_ok());
+
+        assert!(OpeningProof::from_bytes(&proof.to_bytes()[1..]).is_err());
+    }
+
+    #[test]
+    fn test_minimum_amount_proof() {
+        let (c, blinding) = commitment(500);
+
+        for minimum in [0, 499, 500] {
+            let proof = MinimumAmountProof::prove(&c, 500, &blinding, minimum, b"ctx").unwrap();
+            assert!(proof.verify(&c, b"ctx").is_ok());
+        }
+
+        assert!(MinimumAmountProof::prove(&c, 500, &blinding, 501, b"ctx").is_err());
+
+        let mut proof = MinimumAmountProof::prove(&c, 500, &blinding, 400, b"ctx").unwrap();
+        assert!(proof.verify(&c, b"other").is_err());
+        proof.minimum = 450;
+        assert!(proof.verify(&c, b"ctx").is_err());
+    }
+
+    #[test]
+    fn test_minimum_amount_proof_offline() {
+        let (c, blinding) = commitment(u64::MAX);
+        let proof = MinimumAmountProof::prove(&c, u64::MAX, &blinding, 1 << 40, b"").unwrap();
+
+        let decoded = MinimumAmountProof::from_bytes(&proof.to_bytes()).unwrap();
+        assert!(decoded.verify(&c, b"").is_ok());
+
+        let json = serde_json::to_string(&proof).unwrap();
+        let decoded: MinimumAmountProof = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.minimum, 1 << 40);
+        assert!(decoded.verify(&c, b"").is_ok());
+    }
+}
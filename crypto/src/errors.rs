@@ -1,3 +1,4 @@
+use cryptography_types::errors::{BalanceError, ValidationError};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -38,6 +39,48 @@ pub enum CryptoError {
 
     #[error("Invalid output: {0}")]
     InvalidInput(String),
+
+    #[error("Invalid mnemonic: {0}")]
+    InvalidMnemonic(String),
+
+    #[error("Invalid derivation path: {0}")]
+    InvalidDerivationPath(String),
+
+    #[error("Invalid signature")]
+    InvalidSignature,
+
+    #[error("Signature was not produced by the expected address")]
+    SignerMismatch,
+
+    #[error("Not enough decoys: need {needed}, have {available}")]
+    InsufficientDecoys { needed: usize, available: usize },
+
+    #[error("Spend needs {count} inputs, at most {max} allowed")]
+    TooManyInputs { count: usize, max: usize },
+
+    #[error("Insufficient funds: need {needed}, have {available}")]
+    InsufficientFunds { needed: u64, available: u64 },
+
+    #[error("View-only wallet cannot spend or compute key images")]
+    ViewOnly,
+
+    #[error("Range proof error: {0}")]
+    RangeProof(String),
+
+    #[error("Proof verification failed")]
+    ProofVerificationFailed,
+
+    #[error("Keystore error: {0}")]
+    Keystore(String),
+
+    #[error("Wrong password or corrupted keystore")]
+    KeystoreDecryption,
+
+    #[error(transparent)]
+    Balance(#[from] BalanceError),
+
+    #[error(transparent)]
+    Validation(#[from] ValidationError),
 }
 
 pub type Result<T> = std::result::Result<T, CryptoError>;
@@ -0,0 +1,291 @@
+use crate::errors::Result;
+use crate::ethereum::{EthAddress, EthKeyPair};
+use crate::rlp::{encode_bytes, encode_list, encode_uint, encode_uint256};
+use crate::signing::EthSignature;
+use crate::utils::{hash_keccak256, to_hex};
+use serde::{Deserialize, Serialize};
+
+pub const EIP1559_TX_TYPE: u8 = 0x02;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AccessListItem {
+    pub address: EthAddress,
+    pub storage_keys: Vec<[u8; 32]>,
+}
+
+/// Pre-EIP-2718 transaction with EIP-155 replay protection.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LegacyTransaction {
+    pub chain_id: u64,
+    pub nonce: u64,
+    pub gas_price: u128,
+    pub gas_limit: u64,
+    pub to: Option<EthAddress>,
+    pub value: u128,
+    pub data: Vec<u8>,
+}
+
+/// EIP-1559 (type 2) transaction.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Eip1559Transaction {
+    pub chain_id: u64,
+    pub nonce: u64,
+    pub max_priority_fee_per_gas: u128,
+    pub max_fee_per_gas: u128,
+    pub gas_limit: u64,
+    pub to: Option<EthAddress>,
+    pub value: u128,
+    pub data: Vec<u8>,
+    pub access_list: Vec<AccessListItem>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedTransaction {
+    /// Raw bytes for `eth_sendRawTransaction`.
+    pub raw: Vec<u8>,
+    pub hash: [u8; 32],
+    pub signature: EthSignature,
+}
+
+impl SignedTransaction {
+    fn new(raw: Vec<u8>, signature: EthSignature) -> Self {
+        let hash = hash_keccak256(&raw);
+        Self {
+            raw,
+            hash,
+            signature,
+        }
+    }
+
+    pub fn raw_hex(&self) -> String {
+        to_hex(&self.raw)
+    }
+}
+
+impl LegacyTransaction {
+    fn fields(&self) -> Vec<Vec<u8>> {
+        vec![
+            encode_uint(self.nonce as u128),
+            encode_uint(self.gas_price),
+            encode_uint(self.gas_limit as u128),
+            encode_to(&self.to),
+            encode_uint(self.value),
+            encode_bytes(&self.data),
+        ]
+    }
+
+    /// `keccak256(rlp([nonce, gasPrice, gas, to, value, data, chainId, 0, 0]))`
+    pub fn signing_hash(&self) -> [u8; 32] {
+        let mut fields = self.fields();
+        fields.push(encode_uint(self.chain_id as u128));
+        fields.push(encode_uint(0));
+        fields.push(encode_uint(0));
+
+        hash_keccak256(&encode_list(&fields))
+    }
+
+    pub fn encode_signed(&self, signature: &EthSignature) -> Result<SignedTransaction> {
+        let v = signature.recovery_id()? as u128 + 35 + 2 * self.chain_id as u128;
+
+        let mut fields = self.fields();
+        fields.push(encode_uint(v));
+        fields.push(encode_uint256(&signature.r));
+        fields.push(encode_uint256(&signature.s));
+
+        Ok(SignedTransaction::new(encode_list(&fields), *signature))
+    }
+
+    pub fn sign(&self, keypair: &EthKeyPair) -> SignedTransaction {
+        self.encode_signed(&keypair.sign_hash(&self.signing_hash()))
+            .expect("sign_hash produces v in {27, 28}")
+    }
+}
+
+impl Eip1559Transaction {
+    fn fields(&self) -> Vec<Vec<u8>> {
+        let access_list: Vec<Vec<u8>> = self
+            .access_list
+            .iter()
+            .map(|item| {
+                let keys: Vec<Vec<u8>> =
+                    item.storage_keys.iter().map(|k| encode_bytes(k)).collect();
+                encode_list(&[encode_bytes(&item.address), encode_list(&keys)])
+            })
+            .collect();
+
+        vec![
+            encode_uint(self.chain_id as u128),
+            encode_uint(self.nonce as u128),
+            encode_uint(self.max_priority_fee_per_gas),
+            encode_uint(self.max_fee_per_gas),
+            encode_uint(self.gas_limit as u128),
+            encode_to(&self.to),
+            encode_uint(self.value),
+            encode_bytes(&self.data),
+            encode_list(&access_list),
+        ]
+    }
+
+    /// `keccak256(0x02 || rlp([chainId, nonce, maxPriorityFeePerGas,
+    /// maxFeePerGas, gas, to, value, data, accessList]))`
+    pub fn signing_hash(&self) -> [u8; 32] {
+        let mut payload = vec![EIP1559_TX_TYPE];
+        payload.extend_from_slice(&encode_list(&self.fields()));
+
+        hash_keccak256(&payload)
+    }
+
+    pub fn encode_signed(&self, signature: &EthSignature) -> Result<SignedTransaction> {
+        let mut fields = self.fields();
+        fields.push(encode_uint(signature.recovery_id()? as u128));
+        fields.push(encode_uint256(&signature.r));
+        fields.push(encode_uint256(&signature.s));
+
+        let mut raw = vec![EIP1559_TX_TYPE];
+        raw.extend_from_slice(&encode_list(&fields));
+
+        Ok(SignedTransaction::new(raw, *signature))
+    }
+
+    pub fn sign(&self, keypair: &EthKeyPair) -> SignedTransaction {
+        self.encode_signed(&keypair.sign_hash(&self.signing_hash()))
+            .expect("sign_hash produces v in {27, 28}")
+    }
+}
+
+fn encode_to(to: &Option<EthAddress>) -> Vec<u8> {
+    match to {
+        Some(address) => encode_bytes(address),
+        None => encode_bytes(&[]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ethereum::{
+        derive_stealth_secret, generate_stealth_eth, parse_address, scan_stealth_eth,
+    };
+    use crate::signing::recover_address;
+    use crate::utils::from_hex;
+    use secp256k1::SecretKey;
+
+    #[test]
+    fn test_eip155_vector() {
+        // Example from EIP-155
+        let tx = LegacyTransaction {
+            chain_id: 1,
+            nonce: 9,
+            gas_price: 20_000_000_000,
+            gas_limit: 21_000,
+            to: Some([0x35u8; 20]),
+            value: 1_000_000_000_000_000_000,
+            data: vec![],
+        };
+
+        assert_eq!(
+            hex::encode(tx.signing_hash()),
+            "daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53"
+        );
+
+        let keypair =
+            EthKeyPair::from_secret(SecretKey::from_slice(&[0x46u8; 32]).unwrap()).unwrap();
+        let signed = tx.sign(&keypair);
+
+        assert_eq!(
+            signed.raw_hex(),
+            "0xf86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83"
+        );
+    }
+
+    #[test]
+    fn test_eip1559_mainnet_vector() {
+        // Mainnet transaction 0x0ec0b6a2df4d87424e5f6ad2a654e27aaeb7dac20ae9e8385cc09087ad532ee0
+        let tx = Eip1559Transaction {
+            chain_id: 1,
+            nonce: 0x42,
+            max_priority_fee_per_gas: 0x3b9aca00,
+            max_fee_per_gas: 0x4a817c800,
+            gas_limit: 44386,
+            to: Some(parse_address("0x6069a6c32cf691f5982febae4faf8a6f3ab2f0f6").unwrap()),
+            value: 0,
+            data: from_hex("a22cb4650000000000000000000000005eee75727d804a2b13038928d36f8b188945a57a0000000000000000000000000000000000000000000000000000000000000000").unwrap(),
+            access_list: vec![],
+        };
+
+        assert_eq!(
+            hex::encode(tx.signing_hash()),
+            "0d5688ac3897124635b6cf1bc0e29d6dfebceebdc10a54d74f2ef8b56535b682"
+        );
+
+        let mut signature = [0u8; 65];
+        signature[..32].copy_from_slice(
+            &from_hex("840cfc572845f5786e702984c2a582528cad4b49b2a10b9db1be7fca90058565").unwrap(),
+        );
+        signature[32..64].copy_from_slice(
+            &from_hex("25e7109ceb98168d95b09b18bbf6b685130e0562f233877d492b94eee0c5b6d1").unwrap(),
+        );
+        let signature = EthSignature::from_bytes(&signature).unwrap();
+
+        let signed = tx.encode_signed(&signature).unwrap();
+        assert_eq!(
+            hex::encode(signed.hash),
+            "0ec0b6a2df4d87424e5f6ad2a654e27aaeb7dac20ae9e8385cc09087ad532ee0"
+        );
+        assert_eq!(signed.raw[0], EIP1559_TX_TYPE);
+
+        assert_eq!(
+            recover_address(&tx.signing_hash(), &signature).unwrap(),
+            parse_address("0xdd6b8b3dc6b7ad97db52f08a275ff4483e024cea").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_eip1559_access_list() {
+        let mut tx = Eip1559Transaction {
+            chain_id: 1,
+            gas_limit: 30_000,
+            to: Some([0x11u8; 20]),
+            ..Default::default()
+        };
+        let without = tx.signing_hash();
+
+        tx.access_list.push(AccessListItem {
+            address: [0x22u8; 20],
+            storage_keys: vec![[0u8; 32], [1u8; 32]],
+        });
+        assert_ne!(tx.signing_hash(), without);
+    }
+
+    #[test]
+    fn test_sweep_stealth_address() {
+        let view = EthKeyPair::random().unwrap();
+        let spend = EthKeyPair::random().unwrap();
+
+        let (stealth, _) = generate_stealth_eth(&view.public, &spend.public).unwrap();
+        let hs = scan_stealth_eth(&stealth, &view.secret, &spend.public)
+            .unwrap()
+            .unwrap();
+
+        let secret = derive_stealth_secret(&spend.secret, &hs).unwrap();
+        let stealth_keypair = EthKeyPair::from_secret(secret).unwrap();
+        assert_eq!(stealth_keypair.address, stealth.stealth_address);
+
+        let tx = Eip1559Transaction {
+            chain_id: 11155111,
+            nonce: 0,
+            max_priority_fee_per_gas: 1_000_000_000,
+            max_fee_per_gas: 30_000_000_000,
+            gas_limit: 21_000,
+            to: Some([0x42u8; 20]),
+            value: 10_000_000_000_000_000,
+            ..Default::default()
+        };
+
+        let signed = tx.sign(&stealth_keypair);
+        assert_eq!(
+            recover_address(&tx.signing_hash(), &signed.signature).unwrap(),
+            stealth.stealth_address
+        );
+    }
+}
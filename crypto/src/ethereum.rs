@@ -1,9 +1,12 @@
 use crate::errors::{CryptoError, Result};
 use crate::utils::hash_keccak256;
+use cryptography_types::stealth::StealthAddressData;
 use rand::thread_rng;
 use secp256k1::{All, PublicKey, Secp256k1, SecretKey};
 use serde::{Deserialize, Serialize};
 use sha3::{Digest, Keccak256};
+use std::fmt;
+use zeroize::ZeroizeOnDrop;
 
 pub type EthAddress = [u8; 20];
 
@@ -13,13 +16,55 @@ pub struct StealthAddressEth {
     pub stealth_address: EthAddress,
 }
 
-#[derive(Debug, Clone)]
+impl From<StealthAddressEth> for StealthAddressData {
+    fn from(stealth: StealthAddressEth) -> Self {
+        StealthAddressData::new(stealth.ephemeral_pubkey, stealth.stealth_address)
+    }
+}
+
+/// The ephemeral key must be a valid secp256k1 point in its 33-byte
+/// compressed form, the only encoding `generate_stealth_eth` produces.
+impl TryFrom<StealthAddressData> for StealthAddressEth {
+    type Error = CryptoError;
+
+    fn try_from(data: StealthAddressData) -> Result<Self> {
+        let pubkey = PublicKey::from_slice(&data.ephemeral_pubkey)
+            .map_err(|_| CryptoError::InvalidPublicKey)?;
+        if pubkey.serialize()[..] != data.ephemeral_pubkey[..] {
+            return Err(CryptoError::InvalidPublicKey);
+        }
+
+        Ok(Self {
+            ephemeral_pubkey: data.ephemeral_pubkey,
+            stealth_address: data.stealth_address,
+        })
+    }
+}
+
+/// The secret key is erased on drop and left out of Debug output.
 pub struct EthKeyPair {
     pub secret: SecretKey,
     pub public: PublicKey,
     pub address: EthAddress,
 }
 
+impl fmt::Debug for EthKeyPair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EthKeyPair")
+            .field("public", &self.public)
+            .field("address", &format_address(&self.address))
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for EthKeyPair {
+    fn drop(&mut self) {
+        self.secret.non_secure_erase();
+    }
+}
+
+impl ZeroizeOnDrop for EthKeyPair {}
+
 impl EthKeyPair {
     pub fn random() -> Result<Self> {
         let secp = Secp256k1::new();
@@ -64,16 +109,8 @@ pub fn generate_stealth_eth(
     let ephemeral_pubkey = PublicKey::from_secret_key(&secp, &ephemeral_secret);
 
     let shared_secret_point = compute_ecdh(&secp, recipient_view_pubkey, &ephemeral_secret)?;
-
-    let shared_hash = hash_shared_secret(&shared_secret_point);
-    let hs_scalar = SecretKey::from_slice(&shared_hash).map_err(|_| CryptoError::InvalidScalar)?;
-
-    let hs_point = PublicKey::from_secret_key(&secp, &hs_scalar);
-    let stealth_pubkey = hs_point
-        .combine(&recipient_spend_pubkey)
-        .map_err(|_| CryptoError::PointAdditionFailed)?;
-
-    let stealth_address = pubkey_to_address(&stealth_pubkey);
+    let (stealth_address, _) =
+        stealth_address_from_shared(&secp, &shared_secret_point, recipient_spend_pubkey)?;
 
     Ok((
         StealthAddressEth {
@@ -95,16 +132,8 @@ pub fn scan_stealth_eth(
         .map_err(|_| CryptoError::InvalidPublicKey)?;
 
     let shared_secret_point = compute_ecdh(&secp, &ephmeral_pubkey, view_secret)?;
-
-    let shared_hash = hash_shared_secret(&shared_secret_point);
-    let hs_scalar = SecretKey::from_slice(&shared_hash).map_err(|_| CryptoError::InvalidScalar)?;
-
-    let hs_point = PublicKey::from_secret_key(&secp, &hs_scalar);
-    let expected_stealth = hs_point
-        .combine(spend_pubkey)
-        .map_err(|_| CryptoError::PointAdditionFailed)?;
-
-    let expected_address = pubkey_to_address(&expected_stealth);
+    let (expected_address, hs_scalar) =
+        stealth_address_from_shared(&secp, &shared_secret_point, spend_pubkey)?;
 
     if expected_address == stealth_addr.stealth_address {
         Ok(Some(hs_scalar))
@@ -113,6 +142,39 @@ pub fn scan_stealth_eth(
     }
 }
 
+/// Address of `hs*G + spend_pubkey`, where `hs` hashes the ECDH shared point.
+/// Returns `hs` too, the scalar the recipient adds to the spend secret.
+pub(crate) fn stealth_address_from_shared(
+    secp: &Secp256k1<All>,
+    shared_point: &PublicKey,
+    spend_pubkey: &PublicKey,
+) -> Result<(EthAddress, SecretKey)> {
+    let hs_scalar = SecretKey::from_slice(&hash_shared_secret(shared_point))
+        .map_err(|_| CryptoError::InvalidScalar)?;
+    let stealth_pubkey = PublicKey::from_secret_key(secp, &hs_scalar)
+        .combine(spend_pubkey)
+        .map_err(|_| CryptoError::PointAdditionFailed)?;
+
+    Ok((pubkey_to_address(&stealth_pubkey), hs_scalar))
+}
+
+/// Private key of a stealth address found by [`scan_stealth_eth`]:
+/// `spend_secret + hs`.
+pub fn derive_stealth_secret(spend_secret: &SecretKey, hs_scalar: &SecretKey) -> Result<SecretKey> {
+    spend_secret
+        .add_tweak(&(*hs_scalar).into())
+        .map_err(|_| CryptoError::InvalidSecretKey)
+}
+
+/// The 32-byte stealth shared secret, from either side of the exchange:
+/// (recipient view pubkey, ephemeral secret) or (ephemeral pubkey, view secret).
+/// Equal to the bytes of the `hs` scalar returned by [`scan_stealth_eth`].
+pub fn stealth_shared_secret(pubkey: &PublicKey, secret: &SecretKey) -> Result<[u8; 32]> {
+    let secp = Secp256k1::new();
+    let shared_secret_point = compute_ecdh(&secp, pubkey, secret)?;
+    Ok(hash_shared_secret(&shared_secret_point))
+}
+
 pub fn compute_ecdh(
     secp: &Secp256k1<All>,
     pubkey: &PublicKey,
@@ -225,6 +287,15 @@ mod tests {
         assert_eq!(keypair1.address, keypair2.address);
     }
 
+    #[test]
+    fn test_keypair_debug_redacts_secret() {
+        let keypair = EthKeyPair::random().unwrap();
+        let debug = format!("{:?}", keypair);
+
+        assert!(!debug.contains(&hex::encode(keypair.secret.secret_bytes())));
+        assert!(debug.contains(&keypair.address_hex()));
+    }
+
     #[test]
     fn test_stealth_address_generation() {
         let secp = Secp256k1::new();
@@ -393,4 +464,47 @@ mod tests {
 
         println!("ECDH is works");
     }
+
+    #[test]
+    fn test_stealth_shared_secret_matches_scan() {
+        let view = EthKeyPair::random().unwrap();
+        let spend = EthKeyPair::random().unwrap();
+
+        let (stealth, ephemeral_secret) =
+            generate_stealth_eth(&view.public, &spend.public).unwrap();
+        let hs = scan_stealth_eth(&stealth, &view.secret, &spend.public)
+            .unwrap()
+            .unwrap();
+
+        let ephemeral_pubkey = PublicKey::from_slice(&stealth.ephemeral_pubkey).unwrap();
+        let sender = stealth_shared_secret(&view.public, &ephemeral_secret).unwrap();
+        let recipient = stealth_shared_secret(&ephemeral_pubkey, &view.secret).unwrap();
+
+        assert_eq!(sender, recipient);
+        assert_eq!(sender, hs.secret_bytes());
+    }
+
+    #[test]
+    fn test_stealth_address_data_conversion() {
+        let spend = EthKeyPair::random().unwrap();
+        let view = EthKeyPair::random().unwrap();
+        let (stealth, _) = generate_stealth_eth(&view.public, &spend.public).unwrap();
+
+        let data = StealthAddressData::from(stealth.clone());
+        assert_eq!(data.stealth_address, stealth.stealth_address);
+        assert_eq!(StealthAddressEth::try_from(data.clone()).unwrap(), stealth);
+
+        let ephemeral = PublicKey::from_slice(&stealth.ephemeral_pubkey).unwrap();
+        let uncompressed = StealthAddressData {
+            ephemeral_pubkey: ephemeral.serialize_uncompressed().to_vec(),
+            ..data.clone()
+        };
+        assert!(StealthAddressEth::try_from(uncompressed).is_err());
+
+        let garbage = StealthAddressData {
+            ephemeral_pubkey: vec![0x05; 33],
+            ..data
+        };
+        assert!(StealthAddressEth::try_from(garbage).is_err());
+    }
 }
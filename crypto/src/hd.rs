@@ -0,0 +1,288 @@
+use crate::errors::{CryptoError, Result};
+use bip39::Mnemonic;
+use hmac::{Hmac, Mac};
+use secp256k1::{PublicKey, Scalar as SecpScalar, Secp256k1, SecretKey};
+use sha2::Sha512;
+use std::fmt;
+use std::str::FromStr;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+type HmacSha512 = Hmac<Sha512>;
+
+pub const HARDENED_OFFSET: u32 = 0x8000_0000;
+
+// Key roles below `m/44'/60'/{account}'`. The role level is hardened so the
+// stealth keys never overlap with the regular `m/44'/60'/0'/0/i` accounts.
+pub const SPEND_KEY_ROLE: u32 = 0;
+pub const VIEW_KEY_ROLE: u32 = 1;
+pub const RING_KEY_ROLE: u32 = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DerivationPath(Vec<u32>);
+
+impl DerivationPath {
+    pub fn new(indices: Vec<u32>) -> Self {
+        Self(indices)
+    }
+
+    /// `m/44'/60'/{account}'/{role}'/0`
+    pub fn stealth(account: u32, role: u32) -> Self {
+        Self(vec![
+            44 | HARDENED_OFFSET,
+            60 | HARDENED_OFFSET,
+            account | HARDENED_OFFSET,
+            role | HARDENED_OFFSET,
+            0,
+        ])
+    }
+
+    pub fn spend(account: u32) -> Self {
+        Self::stealth(account, SPEND_KEY_ROLE)
+    }
+
+    pub fn view(account: u32) -> Self {
+        Self::stealth(account, VIEW_KEY_ROLE)
+    }
+
+    pub fn ring(account: u32) -> Self {
+        Self::stealth(account, RING_KEY_ROLE)
+    }
+
+    pub fn indices(&self) -> &[u32] {
+        &self.0
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = CryptoError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = s.split('/');
+
+        if parts.next() != Some("m") {
+            return Err(CryptoError::InvalidDerivationPath(format!(
+                "Path must start with 'm': {}",
+                s
+            )));
+        }
+
+        let mut indices = Vec::new();
+        for part in parts {
+            let (digits, hardened) = match part.strip_suffix('\'') {
+                Some(digits) => (digits, true),
+                None => (part, false),
+            };
+
+            let index: u32 = digits
+                .parse()
+                .map_err(|_| CryptoError::InvalidDerivationPath(format!("Bad index: {}", part)))?;
+
+            if index >= HARDENED_OFFSET {
+                return Err(CryptoError::InvalidDerivationPath(format!(
+                    "Index out of range: {}",
+                    part
+                )));
+            }
+
+            indices.push(if hardened {
+                index | HARDENED_OFFSET
+            } else {
+                index
+            });
+        }
+
+        Ok(Self(indices))
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "m")?;
+        for index in &self.0 {
+            if index & HARDENED_OFFSET != 0 {
+                write!(f, "/{}'", index & !HARDENED_OFFSET)?;
+            } else {
+                write!(f, "/{}", index)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// BIP32 extended private key on secp256k1. Erased on drop.
+pub struct ExtendedSecretKey {
+    pub secret: SecretKey,
+    pub chain_code: [u8; 32],
+}
+
+impl Drop for ExtendedSecretKey {
+    fn drop(&mut self) {
+        self.secret.non_secure_erase();
+        self.chain_code.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for ExtendedSecretKey {}
+
+impl ExtendedSecretKey {
+    pub fn master(seed: &[u8]) -> Result<Self> {
+        let mut mac = HmacSha512::new_from_slice(b"Bitcoin seed").expect("HMAC accepts any key");
+        mac.update(seed);
+        let output = mac.finalize().into_bytes();
+
+        Self::from_hmac_output(&output)
+    }
+
+    pub fn derive_child(&self, index: u32) -> Result<Self> {
+        let secp = Secp256k1::new();
+
+        let mut mac = HmacSha512::new_from_slice(&self.chain_code).expect("HMAC accepts any key");
+        if index & HARDENED_OFFSET != 0 {
+            mac.update(&[0u8]);
+            mac.update(&self.secret.secret_bytes());
+        } else {
+            mac.update(&PublicKey::from_secret_key(&secp, &self.secret).serialize());
+        }
+        mac.update(&index.to_be_bytes());
+        let output = mac.finalize().into_bytes();
+
+        let tweak = SecpScalar::from_be_bytes(output[..32].try_into().expect("32 bytes"))
+            .map_err(|_| CryptoError::InvalidScalar)?;
+        let secret = self
+            .secret
+            .add_tweak(&tweak)
+            .map_err(|_| CryptoError::InvalidSecretKey)?;
+
+        let mut chain_code = [0u8; 32];
+        chain_code.copy_from_slice(&output[32..]);
+
+        Ok(Self { secret, chain_code })
+    }
+
+    pub fn derive_path(&self, path: &DerivationPath) -> Result<Self> {
+        let start = Self {
+            secret: self.secret,
+            chain_code: self.chain_code,
+        };
+        path.indices()
+            .iter()
+            .try_fold(start, |key, index| key.derive_child(*index))
+    }
+
+    fn from_hmac_output(output: &[u8]) -> Result<Self> {
+        let secret =
+            SecretKey::from_slice(&output[..32]).map_err(|_| CryptoError::InvalidSecretKey)?;
+
+        let mut chain_code = [0u8; 32];
+        chain_code.copy_from_slice(&output[32..]);
+
+        Ok(Self { secret, chain_code })
+    }
+}
+
+pub fn generate_mnemonic(word_count: usize) -> Result<Mnemonic> {
+    Mnemonic::generate(word_count).map_err(|e| CryptoError::InvalidMnemonic(e.to_string()))
+}
+
+pub fn parse_mnemonic(phrase: &str) -> Result<Mnemonic> {
+    Mnemonic::parse(phrase).map_err(|e| CryptoError::InvalidMnemonic(e.to_string()))
+}
+
+pub fn mnemonic_to_seed(mnemonic: &Mnemonic, passphrase: &str) -> [u8; 64] {
+    mnemonic.to_seed(passphrase)
+}
+
+pub fn derive_secret_key(seed: &[u8], path: &DerivationPath) -> Result<SecretKey> {
+    Ok(ExtendedSecretKey::master(seed)?.derive_path(path)?.secret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ethereum::{checksum_address, EthKeyPair};
+
+    #[test]
+    fn test_bip32_vector_1() {
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+
+        let master = ExtendedSecretKey::master(&seed).unwrap();
+        assert_eq!(
+            hex::encode(master.secret.secret_bytes()),
+            "e8f32e723decf4051aefac8e2c93c9c5b214313817cdb01a1494b917c8436b35"
+        );
+        assert_eq!(
+            hex::encode(master.chain_code),
+            "873dff81c02f525623fd1fe5167eac3a55a049de3d314bb42ee227ffed37d508"
+        );
+
+        let path: DerivationPath = "m/0'/1/2'/2/1000000000".parse().unwrap();
+        let child = master.derive_path(&path).unwrap();
+        assert_eq!(
+            hex::encode(child.secret.secret_bytes()),
+            "471b76e389e528d6de6d816857e012c5455051cad6660850e58372a6c3e6e7c8"
+        );
+    }
+
+    #[test]
+    fn test_bip39_seed_vector() {
+        let mnemonic = parse_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+        )
+        .unwrap();
+
+        let seed = mnemonic_to_seed(&mnemonic, "TREZOR");
+        assert_eq!(
+            hex::encode(seed),
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04"
+        );
+    }
+
+    #[test]
+    fn test_ethereum_account_derivation() {
+        let mnemonic =
+            parse_mnemonic("test test test test test test test test test test test junk").unwrap();
+        let seed = mnemonic_to_seed(&mnemonic, "");
+
+        let path: DerivationPath = "m/44'/60'/0'/0/0".parse().unwrap();
+        let secret = derive_secret_key(&seed, &path).unwrap();
+        let keypair = EthKeyPair::from_secret(secret).unwrap();
+
+        assert_eq!(
+            checksum_address(&keypair.address),
+            "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266"
+        );
+    }
+
+    #[test]
+    fn test_derivation_path_roundtrip() {
+        let path = DerivationPath::spend(3);
+        assert_eq!(path.to_string(), "m/44'/60'/3'/0'/0");
+
+        let parsed: DerivationPath = path.to_string().parse().unwrap();
+        assert_eq!(parsed, path);
+
+        assert_eq!(DerivationPath::view(0).to_string(), "m/44'/60'/0'/1'/0");
+        assert_eq!(DerivationPath::ring(0).to_string(), "m/44'/60'/0'/2'/0");
+    }
+
+    #[test]
+    fn test_derivation_path_invalid() {
+        assert!("44'/60'".parse::<DerivationPath>().is_err());
+        assert!("m/abc".parse::<DerivationPath>().is_err());
+        assert!("m/2147483648".parse::<DerivationPath>().is_err());
+    }
+
+    #[test]
+    fn test_generate_mnemonic() {
+        let mnemonic = generate_mnemonic(24).unwrap();
+        assert_eq!(mnemonic.word_count(), 24);
+
+        let restored = parse_mnemonic(&mnemonic.to_string()).unwrap();
+        assert_eq!(
+            mnemonic_to_seed(&mnemonic, ""),
+            mnemonic_to_seed(&restored, "")
+        );
+
+        assert!(generate_mnemonic(13).is_err());
+    }
+}
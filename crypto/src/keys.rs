@@ -0,0 +1,379 @@
+use crate::errors::{CryptoError, Result};
+use crate::ethereum::{EthAddress, EthKeyPair};
+use crate::hd::{derive_secret_key, mnemonic_to_seed, DerivationPath};
+use crate::secret::Secret;
+use crate::signing::{hash_personal_message, recover_address, EthSignature};
+use crate::utils::{decompress_point, from_hex, hash_keccak256, to_hex};
+use bip39::Mnemonic;
+use cryptography_types::wallet::{ViewingKeyData, WalletKeys, WalletState};
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint, scalar::Scalar,
+};
+use secp256k1::{PublicKey, SecretKey};
+use sha2::{Digest, Sha512};
+use zeroize::Zeroizing;
+
+/// Message users sign with `personal_sign` to derive their stealth keys from
+/// an ordinary Ethereum account. Changing it changes every derived key.
+pub const STEALTH_KEYS_MESSAGE: &str = "Sign this message to access your Gelap stealth keys.\n\nOnly sign this message for a trusted client!";
+
+/// What a sender needs to pay a stealth wallet: the secp256k1 keys for the
+/// stealth address and the Ristretto key that one-time ring keys derive from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StealthMetaAddress {
+    pub view_pubkey: PublicKey,
+    pub spend_pubkey: PublicKey,
+    pub ring_pubkey: RistrettoPoint,
+}
+
+/// The full key set of a stealth wallet: secp256k1 spend and view keys for
+/// stealth addresses, and the Ristretto key used in ring signatures.
+#[derive(Debug)]
+pub struct StealthKeys {
+    pub spend: EthKeyPair,
+    pub view: EthKeyPair,
+    pub ring_secret: Secret<Scalar>,
+}
+
+impl StealthKeys {
+    pub fn from_mnemonic(mnemonic: &Mnemonic, passphrase: &str, account: u32) -> Result<Self> {
+        let seed = Zeroizing::new(mnemonic_to_seed(mnemonic, passphrase));
+        Self::from_seed(seed.as_ref(), account)
+    }
+
+    pub fn from_seed(seed: &[u8], account: u32) -> Result<Self> {
+        let spend = derive_secret_key(seed, &DerivationPath::spend(account))?;
+        let view = derive_secret_key(seed, &DerivationPath::view(account))?;
+        let ring = derive_secret_key(seed, &DerivationPath::ring(account))?;
+
+        Ok(Self {
+            spend: EthKeyPair::from_secret(spend)?,
+            view: EthKeyPair::from_secret(view)?,
+            ring_secret: Secret::new(ring_secret_from_bytes(&ring.secret_bytes())),
+        })
+    }
+
+    /// Umbra-style derivation from a `personal_sign` signature over
+    /// [`STEALTH_KEYS_MESSAGE`]: spend = keccak256(r), view = keccak256(s).
+    ///
+    /// The signature must recover to `address`. It is normalized to low-s
+    /// first so a malleated signature yields the same keys.
+    pub fn from_signature(address: &EthAddress, signature: &EthSignature) -> Result<Self> {
+        let hash = hash_personal_message(STEALTH_KEYS_MESSAGE.as_bytes());
+        if recover_address(&hash, signature)? != *address {
+            return Err(CryptoError::SignerMismatch);
+        }
+
+        let signature = signature.normalized()?;
+
+        let spend = SecretKey::from_slice(&hash_keccak256(&signature.r))
+            .map_err(|_| CryptoError::InvalidSecretKey)?;
+        let view = SecretKey::from_slice(&hash_keccak256(&signature.s))
+            .map_err(|_| CryptoError::InvalidSecretKey)?;
+
+        let mut rs = Zeroizing::new([0u8; 64]);
+        rs[..32].copy_from_slice(&signature.r);
+        rs[32..].copy_from_slice(&signature.s);
+
+        Ok(Self {
+            spend: EthKeyPair::from_secret(spend)?,
+            view: EthKeyPair::from_secret(view)?,
+            ring_secret: Secret::new(ring_secret_from_bytes(rs.as_ref())),
+        })
+    }
+
+    pub fn from_eth_keypair(keypair: &EthKeyPair) -> Result<Self> {
+        let signature = keypair.personal_sign(STEALTH_KEYS_MESSAGE.as_bytes());
+        Self::from_signature(&keypair.address, &signature)
+    }
+
+    pub fn ring_public(&self) -> RistrettoPoint {
+        self.ring_secret.expose() * RISTRETTO_BASEPOINT_POINT
+    }
+
+    pub fn meta_address(&self) -> StealthMetaAddress {
+        StealthMetaAddress {
+            view_pubkey: self.view.public,
+            spend_pubkey: self.spend.public,
+            ring_pubkey: self.ring_public(),
+        }
+    }
+
+    pub fn viewing_key(&self) -> ViewingKey {
+        ViewingKey {
+            view: EthKeyPair {
+                secret: self.view.secret,
+                public: self.view.public,
+                address: self.view.address,
+            },
+            spend_pubkey: self.spend.public,
+            ring_pubkey: self.ring_public(),
+        }
+    }
+
+    pub fn to_wallet_state(&self) -> WalletState {
+        WalletState {
+            keys: WalletKeys::Full {
+                view_secret: self.view.secret.secret_bytes(),
+                spend_secret: self.spend.secret.secret_bytes(),
+                ring_secret: self.ring_secret.expose().to_bytes(),
+            },
+            outputs: Vec::new(),
+        }
+    }
+
+    /// Fails with [`CryptoError::ViewOnly`] for a view-only state.
+    pub fn from_wallet_state(state: &WalletState) -> Result<Self> {
+        let WalletKeys::Full {
+            view_secret,
+            spend_secret,
+            ring_secret,
+        } = &state.keys
+        else {
+            return Err(CryptoError::ViewOnly);
+        };
+
+        let spend =
+            SecretKey::from_slice(spend_secret).map_err(|_| CryptoError::InvalidSecretKey)?;
+        let view = SecretKey::from_slice(view_secret).map_err(|_| CryptoError::InvalidSecretKey)?;
+        let ring_secret = Option::from(Scalar::from_canonical_bytes(*ring_secret))
+            .ok_or(CryptoError::InvalidScalar)?;
+
+        Ok(Self {
+            spend: EthKeyPair::from_secret(spend)?,
+            view: EthKeyPair::from_secret(view)?,
+            ring_secret: Secret::new(ring_secret),
+        })
+    }
+}
+
+/// Lets auditors and watch-only services find incoming outputs and open their
+/// amounts. Without the spend and ring secrets it can neither spend nor
+/// compute key images, so it cannot tell when an output is spent.
+#[derive(Debug)]
+pub struct ViewingKey {
+    pub view: EthKeyPair,
+    pub spend_pubkey: PublicKey,
+    pub ring_pubkey: RistrettoPoint,
+}
+
+impl ViewingKey {
+    /// view secret (32) || compressed spend public key (33) || ring public key (32)
+    pub const ENCODED_LEN: usize = 97;
+
+    pub fn meta_address(&self) -> StealthMetaAddress {
+        StealthMetaAddress {
+            view_pubkey: self.view.public,
+            spend_pubkey: self.spend_pubkey,
+            ring_pubkey: self.ring_pubkey,
+        }
+    }
+
+    /// 0x-prefixed hex of the encoded key. It contains the view secret, so it
+    /// must be handled like one.
+    pub fn export(&self) -> String {
+        let mut bytes = Zeroizing::new(Vec::with_capacity(Self::ENCODED_LEN));
+        bytes.extend_from_slice(&self.view.secret.secret_bytes());
+        bytes.extend_from_slice(&self.spend_pubkey.serialize());
+        bytes.extend_from_slice(self.ring_pubkey.compress().as_bytes());
+        to_hex(&bytes)
+    }
+
+    pub fn import(encoded: &str) -> Result<Self> {
+        let bytes = Zeroizing::new(
+            from_hex(encoded).map_err(|e| CryptoError::Deserialization(e.to_string()))?,
+        );
+        if bytes.len() != Self::ENCODED_LEN {
+            return Err(CryptoError::Deserialization(format!(
+                "Viewing key must be {} bytes, got {}",
+                Self::ENCODED_LEN,
+                bytes.len()
+            )));
+        }
+
+        Self::from_data(&ViewingKeyData {
+            view_secret: bytes[..32].try_into().expect("32 bytes"),
+            spend_pubkey: bytes[32..65].to_vec(),
+            ring_pubkey: bytes[65..].try_into().expect("32 bytes"),
+        })
+    }
+
+    pub fn to_data(&self) -> ViewingKeyData {
+        ViewingKeyData {
+            view_secret: self.view.secret.secret_bytes(),
+            spend_pubkey: self.spend_pubkey.serialize().to_vec(),
+            ring_pubkey: self.ring_pubkey.compress().to_bytes(),
+        }
+    }
+
+    /// The spend key must be in its 33-byte compressed form.
+    pub fn from_data(data: &ViewingKeyData) -> Result<Self> {
+        let view =
+            SecretKey::from_slice(&data.view_secret).map_err(|_| CryptoError::InvalidSecretKey)?;
+        let spend_pubkey =
+            PublicKey::from_slice(&data.spend_pubkey).map_err(|_| CryptoError::InvalidPublicKey)?;
+        if spend_pubkey.serialize()[..] != data.spend_pubkey[..] {
+            return Err(CryptoError::InvalidPublicKey);
+        }
+
+        Ok(Self {
+            view: EthKeyPair::from_secret(view)?,
+            spend_pubkey,
+            ring_pubkey: decompress_point(&data.ring_pubkey)?,
+        })
+    }
+
+    pub fn to_wallet_state(&self) -> WalletState {
+        WalletState {
+            keys: WalletKeys::ViewOnly(self.to_data()),
+            outputs: Vec::new(),
+        }
+    }
+
+    /// Works for both modes; a full state yields its viewing key.
+    pub fn from_wallet_state(state: &WalletState) -> Result<Self> {
+        match &state.keys {
+            WalletKeys::Full { .. } => Ok(StealthKeys::from_wallet_state(state)?.viewing_key()),
+            WalletKeys::ViewOnly(data) => Self::from_data(data),
+        }
+    }
+}
+
+/// Maps 32 bytes of secret key material to a Ristretto scalar.
+pub fn ring_secret_from_bytes(bytes: &[u8]) -> Scalar {
+    let mut hasher = Sha512::new();
+    hasher.update(b"GELAP_RING_KEY_V1");
+    hasher.update(bytes);
+    let hash = hasher.finalize();
+
+    Scalar::from_bytes_mod_order_wide(&hash.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hd::{generate_mnemonic, parse_mnemonic};
+    use crate::signing::sign_hash;
+    use cryptography_types::codec::WireFormat;
+
+    #[test]
+    fn test_restore_from_mnemonic() {
+        let mnemonic = generate_mnemonic(12).unwrap();
+        let keys = StealthKeys::from_mnemonic(&mnemonic, "", 0).unwrap();
+
+        let restored_mnemonic = parse_mnemonic(&mnemonic.to_string()).unwrap();
+        let restored = StealthKeys::from_mnemonic(&restored_mnemonic, "", 0).unwrap();
+
+        assert_eq!(keys.spend.address, restored.spend.address);
+        assert_eq!(keys.view.address, restored.view.address);
+        assert_eq!(keys.ring_secret.expose(), restored.ring_secret.expose());
+
+        let state = keys.to_wallet_state();
+        let restored_state = restored.to_wallet_state();
+        assert_eq!(state.to_wire(), restored_state.to_wire());
+    }
+
+    #[test]
+    fn test_keys_are_distinct() {
+        let mnemonic = generate_mnemonic(12).unwrap();
+        let keys = StealthKeys::from_mnemonic(&mnemonic, "", 0).unwrap();
+
+        assert_ne!(keys.spend.secret, keys.view.secret);
+
+        let other_account = StealthKeys::from_mnemonic(&mnemonic, "", 1).unwrap();
+        assert_ne!(keys.spend.address, other_account.spend.address);
+        assert_ne!(
+            keys.ring_secret.expose(),
+            other_account.ring_secret.expose()
+        );
+
+        let with_passphrase = StealthKeys::from_mnemonic(&mnemonic, "hunter2", 0).unwrap();
+        assert_ne!(keys.spend.address, with_passphrase.spend.address);
+    }
+
+    #[test]
+    fn test_from_eth_keypair_deterministic() {
+        let account = EthKeyPair::random().unwrap();
+
+        let keys1 = StealthKeys::from_eth_keypair(&account).unwrap();
+        let keys2 = StealthKeys::from_eth_keypair(&account).unwrap();
+
+        assert_eq!(keys1.spend.address, keys2.spend.address);
+        assert_eq!(keys1.view.address, keys2.view.address);
+        assert_eq!(keys1.ring_secret.expose(), keys2.ring_secret.expose());
+
+        assert_ne!(keys1.spend.address, account.address);
+        assert_ne!(keys1.spend.secret, keys1.view.secret);
+    }
+
+    #[test]
+    fn test_from_signature_wrong_signer() {
+        let account = EthKeyPair::random().unwrap();
+        let other = EthKeyPair::random().unwrap();
+
+        let hash = hash_personal_message(STEALTH_KEYS_MESSAGE.as_bytes());
+        let signature = sign_hash(&other.secret, &hash);
+
+        assert!(matches!(
+            StealthKeys::from_signature(&account.address, &signature),
+            Err(CryptoError::SignerMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_from_signature_wrong_message() {
+        let account = EthKeyPair::random().unwrap();
+        let signature = sign_hash(&account.secret, &hash_personal_message(b"something else"));
+
+        assert!(StealthKeys::from_signature(&account.address, &signature).is_err());
+    }
+
+    #[test]
+    fn test_wallet_state_roundtrip() {
+        let mnemonic = generate_mnemonic(24).unwrap();
+        let keys = StealthKeys::from_mnemonic(&mnemonic, "", 0).unwrap();
+
+        let state = keys.to_wallet_state();
+        let restored = StealthKeys::from_wallet_state(&state).unwrap();
+
+        assert_eq!(keys.spend.public, restored.spend.public);
+        assert_eq!(keys.view.public, restored.view.public);
+        assert_eq!(keys.ring_public(), restored.ring_public());
+    }
+
+    #[test]
+    fn test_viewing_key_export_import() {
+        let keys = StealthKeys::from_seed(&[9u8; 64], 0).unwrap();
+        let viewing_key = keys.viewing_key();
+
+        let exported = viewing_key.export();
+        assert_eq!(exported.len(), 2 + 2 * ViewingKey::ENCODED_LEN);
+
+        let imported = ViewingKey::import(&exported).unwrap();
+        assert_eq!(imported.meta_address(), keys.meta_address());
+        assert_eq!(imported.export(), exported);
+
+        assert!(ViewingKey::import(&exported[..exported.len() - 2]).is_err());
+        // Ring key bytes that are not a valid point
+        let corrupted = format!("{}{}", &exported[..exported.len() - 64], "ff".repeat(32));
+        assert!(ViewingKey::import(&corrupted).is_err());
+    }
+
+    #[test]
+    fn test_view_only_state_has_no_spend_keys() {
+        let keys = StealthKeys::from_seed(&[9u8; 64], 0).unwrap();
+        let state = keys.viewing_key().to_wallet_state();
+
+        assert!(state.is_view_only());
+        assert!(matches!(
+            StealthKeys::from_wallet_state(&state),
+            Err(CryptoError::ViewOnly)
+        ));
+        let viewing_key = ViewingKey::from_wallet_state(&state).unwrap();
+        assert_eq!(viewing_key.meta_address(), keys.meta_address());
+
+        // A full state yields the same viewing key
+        let from_full = ViewingKey::from_wallet_state(&keys.to_wallet_state()).unwrap();
+        assert_eq!(from_full.export(), viewing_key.export());
+    }
+}
@@ -0,0 +1,491 @@
+// Password-encrypted wallet file, laid out like an Ethereum V3 keystore but
+// with an AEAD cipher: scrypt derives a 32-byte key from the password and
+// ChaCha20-Poly1305 encrypts the wire encoding of the `WalletState`.
+//
+// The KDF parameters and cipher name are bound as associated data, so editing
+// them in the file fails decryption just like a wrong password does.
+
+use crate::errors::{CryptoError, Result};
+use crate::keys::ViewingKey;
+use crate::utils::{from_hex, random_bytes, to_hex};
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use cryptography_types::{codec::WireFormat, wallet::WalletState};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+pub const KEYSTORE_VERSION: u32 = 1;
+
+const KDF: &str = "scrypt";
+const CIPHER: &str = "chacha20-poly1305";
+const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+
+/// Upper bounds on the scrypt cost, so a crafted file cannot make decryption
+/// allocate or run without limit. The per-field bounds alone still allow
+/// `128 * r * n` bytes of 4 GiB, so that product is capped by
+/// [`MAX_KDF_MEMORY`] as well; `n = 2^20, r = 8` is right at the limit.
+pub const MAX_LOG_N: u8 = 20;
+pub const MAX_R: u32 = 32;
+pub const MAX_P: u32 = 4;
+pub const MAX_KDF_MEMORY: u64 = 1 << 30;
+
+/// scrypt cost. The defaults match geth's standard keystore (`n = 2^18`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            log_n: 18,
+            r: 8,
+            p: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Keystore {
+    pub version: u32,
+    pub crypto: KeystoreCrypto,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeystoreCrypto {
+    pub cipher: String,
+    pub ciphertext: String,
+    pub cipherparams: CipherParams,
+    pub kdf: String,
+    pub kdfparams: ScryptParams,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CipherParams {
+    pub nonce: String,
+}
+
+impl KdfParams {
+    fn check(&self) -> Result<()> {
+        if self.log_n == 0 || self.log_n > MAX_LOG_N {
+            return Err(CryptoError::Keystore(format!(
+                "scrypt n must be between 2^1 and 2^{}",
+                MAX_LOG_N
+            )));
+        }
+        if self.r == 0 || self.r > MAX_R {
+            return Err(CryptoError::Keystore(format!(
+                "scrypt r must be between 1 and {}",
+                MAX_R
+            )));
+        }
+        if self.p == 0 || self.p > MAX_P {
+            return Err(CryptoError::Keystore(format!(
+                "scrypt p must be between 1 and {}",
+                MAX_P
+            )));
+        }
+        if self.memory() > MAX_KDF_MEMORY {
+            return Err(CryptoError::Keystore(format!(
+                "scrypt needs {} bytes, more than the {} byte limit",
+                self.memory(),
+                MAX_KDF_MEMORY
+            )));
+        }
+        Ok(())
+    }
+
+    /// Bytes scrypt allocates for its `n` blocks of `128 * r` bytes. Call
+    /// only after `log_n` and `r` are bounded.
+    fn memory(&self) -> u64 {
+        (128 * u64::from(self.r)) << self.log_n
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScryptParams {
+    pub dklen: usize,
+    pub n: u64,
+    pub r: u32,
+    pub p: u32,
+    pub salt: String,
+}
+
+impl ScryptParams {
+    fn kdf_params(&self) -> Result<KdfParams> {
+        if !self.n.is_power_of_two() {
+            return Err(CryptoError::Keystore(
+                "scrypt n must be a power of two".into(),
+            ));
+        }
+        let params = KdfParams {
+            log_n: self.n.trailing_zeros() as u8,
+            r: self.r,
+            p: self.p,
+        };
+        params.check()?;
+        Ok(params)
+    }
+}
+
+impl Keystore {
+    pub fn encrypt(state: &WalletState, password: &str) -> Result<Self> {
+        Self::encrypt_with_params(state, password, KdfParams::default())
+    }
+
+    pub fn encrypt_with_params(
+        state: &WalletState,
+        password: &str,
+        params: KdfParams,
+    ) -> Result<Self> {
+        params.check()?;
+        let salt: [u8; SALT_LEN] = random_bytes();
+        let nonce: [u8; NONCE_LEN] = random_bytes();
+
+        let kdfparams = ScryptParams {
+            dklen: KEY_LEN,
+            n: 1u64 << params.log_n,
+            r: params.r,
+            p: params.p,
+            salt: to_hex(&salt),
+        };
+        let mut keystore = Self {
+            version: KEYSTORE_VERSION,
+            crypto: KeystoreCrypto {
+                cipher: CIPHER.to_string(),
+                ciphertext: String::new(),
+                cipherparams: CipherParams {
+                    nonce: to_hex(&nonce),
+                },
+                kdf: KDF.to_string(),
+                kdfparams,
+            },
+        };
+
+        let key = derive_key(password, &keystore.crypto.kdfparams)?;
+        let ciphertext = ChaCha20Poly1305::new(&Key::from(*key))
+            .encrypt(
+                &Nonce::from(nonce),
+                Payload {
+                    msg: &Zeroizing::new(state.to_wire()),
+                    aad: &keystore.associated_data(),
+                },
+            )
+            .map_err(|_| CryptoError::Keystore("Encryption failed".into()))?;
+        keystore.crypto.ciphertext = to_hex(&ciphertext);
+
+        Ok(keystore)
+    }
+
+    /// Fails with [`CryptoError::KeystoreDecryption`] on a wrong password or
+    /// any modification of the file.
+    pub fn decrypt(&self, password: &str) -> Result<WalletState> {
+        self.check_header()?;
+
+        let nonce: [u8; NONCE_LEN] = decode_hex(&self.crypto.cipherparams.nonce, "nonce")?
+            .try_into()
+            .map_err(|_| CryptoError::Keystore(format!("Nonce must be {} bytes", NONCE_LEN)))?;
+        let ciphertext = decode_hex(&self.crypto.ciphertext, "ciphertext")?;
+
+        let key = derive_key(password, &self.crypto.kdfparams)?;
+        let plaintext = ChaCha20Poly1305::new(&Key::from(*key))
+            .decrypt(
+                &Nonce::from(nonce),
+                Payload {
+                    msg: &ciphertext,
+                    aad: &self.associated_data(),
+                },
+            )
+            .map(Zeroizing::new)
+            .map_err(|_| CryptoError::KeystoreDecryption)?;
+
+        let state = WalletState::from_wire(&plaintext)
+            .map_err(|e| CryptoError::Keystore(format!("Invalid wallet state: {}", e)))?;
+        // Refuse to hand out secrets that are not valid keys
+        ViewingKey::from_wallet_state(&state)?;
+
+        Ok(state)
+    }
+
+    /// Re-encrypts under `new_password` with a fresh salt and nonce, keeping
+    /// the KDF cost of this keystore.
+    pub fn change_password(&self, old_password: &str, new_password: &str) -> Result<Self> {
+        let state = self.decrypt(old_password)?;
+        let params = self.crypto.kdfparams.kdf_params()?;
+        Self::encrypt_with_params(&state, new_password, params)
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| CryptoError::SerilizationError(e.to_string()))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let keystore: Self =
+            serde_json::from_str(json).map_err(|e| CryptoError::Deserialization(e.to_string()))?;
+        keystore.check_header()?;
+        Ok(keystore)
+    }
+
+    fn check_header(&self) -> Result<()> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(CryptoError::Keystore(format!(
+                "Unsupported keystore version {}",
+                self.version
+            )));
+        }
+        if self.crypto.kdf != KDF {
+            return Err(CryptoError::Keystore(format!(
+                "Unsupported KDF {}",
+                self.crypto.kdf
+            )));
+        }
+        if self.crypto.cipher != CIPHER {
+            return Err(CryptoError::Keystore(format!(
+                "Unsupported cipher {}",
+                self.crypto.cipher
+            )));
+        }
+        if self.crypto.kdfparams.dklen != KEY_LEN {
+            return Err(CryptoError::Keystore(format!(
+                "Derived key must be {} bytes",
+                KEY_LEN
+            )));
+        }
+        self.crypto.kdfparams.kdf_params()?;
+        Ok(())
+    }
+
+    // Everything except the ciphertext and nonce, which the AEAD covers itself
+    fn associated_data(&self) -> Vec<u8> {
+        let params = &self.crypto.kdfparams;
+        let mut aad = Vec::new();
+        aad.extend_from_slice(&self.version.to_be_bytes());
+        aad.extend_from_slice(self.crypto.cipher.as_bytes());
+        aad.push(0);
+        aad.extend_from_slice(self.crypto.kdf.as_bytes());
+        aad.push(0);
+        aad.extend_from_slice(&(params.dklen as u32).to_be_bytes());
+        aad.extend_from_slice(&params.n.to_be_bytes());
+        aad.extend_from_slice(&params.r.to_be_bytes());
+        aad.extend_from_slice(&params.p.to_be_bytes());
+        aad.extend_from_slice(params.salt.to_lowercase().as_bytes());
+        aad
+    }
+}
+
+fn derive_key(password: &str, params: &ScryptParams) -> Result<Zeroizing<[u8; KEY_LEN]>> {
+    let salt = decode_hex(&params.salt, "salt")?;
+    if salt.len() != SALT_LEN {
+        return Err(CryptoError::Keystore(format!(
+            "Salt must be {} bytes",
+            SALT_LEN
+        )));
+    }
+
+    let KdfParams { log_n, r, p } = params.kdf_params()?;
+    let scrypt_params = scrypt::Params::new(log_n, r, p, KEY_LEN)
+        .map_err(|e| CryptoError::Keystore(format!("Invalid scrypt parameters: {}", e)))?;
+
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    scrypt::scrypt(password.as_bytes(), &salt, &scrypt_params, key.as_mut())
+        .map_err(|e| CryptoError::Keystore(format!("Key derivation failed: {}", e)))?;
+    Ok(key)
+}
+
+fn decode_hex(s: &str, field: &str) -> Result<Vec<u8>> {
+    from_hex(s).map_err(|e| CryptoError::Keystore(format!("Invalid {}: {}", field, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::StealthKeys;
+    use cryptography_types::wallet::WalletKeys;
+
+    // Cheap parameters so tests stay fast
+    const TEST_PARAMS: KdfParams = KdfParams {
+        log_n: 4,
+        r: 8,
+        p: 1,
+    };
+
+    fn state() -> WalletState {
+        StealthKeys::from_seed(&[7u8; 64], 0)
+            .unwrap()
+            .to_wallet_state()
+    }
+
+    fn spend_secret(state: &WalletState) -> [u8; 32] {
+        match &state.keys {
+            WalletKeys::Full { spend_secret, .. } => *spend_secret,
+            WalletKeys::ViewOnly(_) => panic!("expected a full wallet"),
+        }
+    }
+
+    fn keystore() -> Keystore {
+        Keystore::encrypt_with_params(&state(), "correct horse", TEST_PARAMS).unwrap()
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let keystore = keystore();
+        let json = keystore.to_json().unwrap();
+
+        // No secret appears in the file
+        let secret = hex::encode(spend_secret(&state()));
+        assert!(!json.contains(&secret));
+        assert!(json.contains("\"kdf\": \"scrypt\""));
+
+        let decrypted = Keystore::from_json(&json)
+            .unwrap()
+            .decrypt("correct horse")
+            .unwrap();
+        assert_eq!(decrypted.to_wire(), state().to_wire());
+    }
+
+    #[test]
+    fn test_view_only_roundtrip() {
+        let viewing_key = StealthKeys::from_seed(&[7u8; 64], 0).unwrap().viewing_key();
+        let keystore =
+            Keystore::encrypt_with_params(&viewing_key.to_wallet_state(), "pw", TEST_PARAMS)
+                .unwrap();
+
+        let decrypted = keystore.decrypt("pw").unwrap();
+        assert!(decrypted.is_view_only());
+        assert_eq!(decrypted.to_wire(), viewing_key.to_wallet_state().to_wire());
+    }
+
+    #[test]
+    fn test_wrong_password() {
+        assert!(matches!(
+            keystore().decrypt("wrong"),
+            Err(CryptoError::KeystoreDecryption)
+        ));
+    }
+
+    #[test]
+    fn test_tampering_detected() {
+        let keystore = keystore();
+
+        let mut tampered = keystore.clone();
+        let mut ciphertext = from_hex(&tampered.crypto.ciphertext).unwrap();
+        ciphertext[0] ^= 1;
+        tampered.crypto.ciphertext = to_hex(&ciphertext);
+        assert!(matches!(
+            tampered.decrypt("correct horse"),
+            Err(CryptoError::KeystoreDecryption)
+        ));
+
+        // Lowering the KDF cost is caught by the associated data
+        let mut tampered = keystore.clone();
+        tampered.crypto.kdfparams.p = 2;
+        assert!(matches!(
+            tampered.decrypt("correct horse"),
+            Err(CryptoError::KeystoreDecryption)
+        ));
+
+        let mut tampered = keystore;
+        tampered.version = 2;
+        assert!(matches!(
+            tampered.decrypt("correct horse"),
+            Err(CryptoError::Keystore(_))
+        ));
+    }
+
+    #[test]
+    fn test_change_password() {
+        let keystore = keystore();
+        let changed = keystore
+            .change_password("correct horse", "battery")
+            .unwrap();
+
+        assert_ne!(
+            changed.crypto.kdfparams.salt,
+            keystore.crypto.kdfparams.salt
+        );
+        assert_eq!(changed.crypto.kdfparams.n, 16);
+        assert!(changed.decrypt("correct horse").is_err());
+        assert_eq!(
+            spend_secret(&changed.decrypt("battery").unwrap()),
+            spend_secret(&state())
+        );
+
+        assert!(keystore.change_password("wrong", "battery").is_err());
+    }
+
+    #[test]
+    fn test_kdf_params_bounded() {
+        for params in [
+            KdfParams {
+                log_n: 64,
+                ..TEST_PARAMS
+            },
+            KdfParams {
+                log_n: MAX_LOG_N + 1,
+                ..TEST_PARAMS
+            },
+            KdfParams {
+                r: 0,
+                ..TEST_PARAMS
+            },
+            KdfParams {
+                p: MAX_P + 1,
+                ..TEST_PARAMS
+            },
+            // Each field is in range, but this would take 4 GiB
+            KdfParams {
+                log_n: 20,
+                r: 32,
+                p: 1,
+            },
+        ] {
+            assert!(matches!(
+                Keystore::encrypt_with_params(&state(), "pw", params),
+                Err(CryptoError::Keystore(_))
+            ));
+        }
+
+        // A crafted file is rejected before any key derivation
+        for n in [0, 24, 1 << 40] {
+            let mut crafted = keystore();
+            crafted.crypto.kdfparams.n = n;
+            assert!(matches!(
+                crafted.decrypt("correct horse"),
+                Err(CryptoError::Keystore(_))
+            ));
+        }
+        let mut crafted = keystore();
+        crafted.crypto.kdfparams.r = u32::MAX;
+        assert!(matches!(
+            crafted.decrypt("correct horse"),
+            Err(CryptoError::Keystore(_))
+        ));
+
+        let largest = KdfParams {
+            log_n: 20,
+            r: 8,
+            p: MAX_P,
+        };
+        assert_eq!(largest.memory(), MAX_KDF_MEMORY);
+        assert!(largest.check().is_ok());
+    }
+
+    #[test]
+    fn test_from_json_rejects_unknown_format() {
+        let json = keystore()
+            .to_json()
+            .unwrap()
+            .replace("chacha20-poly1305", "aes-128-ctr");
+        assert!(matches!(
+            Keystore::from_json(&json),
+            Err(CryptoError::Keystore(_))
+        ));
+    }
+}
@@ -2,27 +2,88 @@
 
 // Declare modules
 pub mod bridge;
+pub mod coin_selection;
+pub mod disclosure;
 pub mod errors;
+pub mod eth_transaction;
 pub mod ethereum;
+pub mod hd;
+pub mod keys;
+pub mod keystore;
+pub mod note;
+pub mod payment_proof;
 pub mod pedersen;
+pub mod reserve;
 pub mod ring_signature;
+pub mod rlp;
+pub mod secret;
+pub mod serde_hex;
+pub mod sigma;
+pub mod signing;
+#[cfg(test)]
+mod test_utils;
+pub mod transfer;
 pub mod utils;
+pub mod wallet;
 pub mod zkproof;
 
+// The zkVM guest's own copies of the hashes it shares with this crate,
+// compiled in so the tests can check that both sides agree
+#[cfg(test)]
+#[path = "../../zkvm/src/hash.rs"]
+mod guest_hash;
+
 // Re-export commonly used items
 pub use errors::{CryptoError, Result};
 
+pub use secret::Secret;
+
+// Disclosure proofs
+pub use disclosure::{MinimumAmountProof, OpeningProof};
+pub use payment_proof::PaymentProof;
+pub use reserve::{ReserveInput, ReserveProof, RingMember, MAX_RING_SIZE};
+
 // Pedersen commitment exports
 pub use pedersen::{commit, generate_blinding, verify_commitment, PedersenCommitment};
 
 // Ethereum module exports
 pub use ethereum::{
-    checksum_address, format_address, generate_stealth_eth, parse_address, pubkey_to_address,
-    scan_stealth_eth, EthAddress, EthKeyPair, StealthAddressEth,
+    checksum_address, derive_stealth_secret, format_address, generate_stealth_eth, parse_address,
+    pubkey_to_address, scan_stealth_eth, stealth_shared_secret, EthAddress, EthKeyPair,
+    StealthAddressEth,
 };
 
+// Ethereum transaction exports
+pub use eth_transaction::{
+    AccessListItem, Eip1559Transaction, LegacyTransaction, SignedTransaction,
+};
+
+// Key derivation exports
+pub use hd::{generate_mnemonic, parse_mnemonic, DerivationPath, ExtendedSecretKey};
+pub use keys::{StealthKeys, StealthMetaAddress, ViewingKey, STEALTH_KEYS_MESSAGE};
+pub use keystore::{
+    KdfParams, Keystore, KEYSTORE_VERSION, MAX_KDF_MEMORY, MAX_LOG_N, MAX_P, MAX_R,
+};
+
+// Ethereum message signing exports
+pub use signing::{
+    hash_personal_message, hash_typed_data, recover_address, recover_personal, recover_typed_data,
+    verify_personal, verify_typed_data, Eip712Domain, Eip712Struct, EthSignature,
+};
+
+// Transfer building exports
+pub use coin_selection::{CoinSelector, Selection, SelectionStrategy};
+pub use note::{decrypt_note, encrypt_note, output_key_offset, output_ring_pubkey};
+pub use transfer::{TransferBuilder, DEFAULT_RING_SIZE};
+pub use wallet::Wallet;
+
 // Ring signature module exports
-pub use ring_signature::{sign_ring, verify_ring, RingSignature};
+pub use ring_signature::{
+    compute_key_image, prove_key_image, sign_ring, verify_key_image, verify_ring, RingSignature,
+};
+
+// Sigma protocol exports
+pub use sigma::{DleqProof, DleqStatement, SchnorrProof, SchnorrStatement, TranscriptProtocol};
 
 // Bridge module exports
 pub use bridge::{address_to_ristretto, hash_to_ristretto, secp256k1_to_ristretto};
@@ -39,4 +100,24 @@ mod tests {
         let _blinding = generate_blinding();
         println!("All modules accessible");
     }
+
+    #[test]
+    fn test_guest_hashes_match() {
+        assert_eq!(guest_hash::get_h_generator(), pedersen::get_h_generator());
+
+        for _ in 0..8 {
+            let point =
+                generate_blinding() * curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+            assert_eq!(
+                guest_hash::hash_to_point(&point),
+                ring_signature::hash_to_point(&point)
+            );
+
+            let other = guest_hash::hash_to_point(&point);
+            assert_eq!(
+                guest_hash::hash_challenge(b"txid", &point, &other),
+                ring_signature::hash_challenge(b"txid", &point, &other)
+            );
+        }
+    }
 }
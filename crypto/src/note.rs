@@ -0,0 +1,121 @@
+// Output notes: the one-time ring key of an output and its commitment
+// opening, encrypted to the recipient under the stealth shared secret.
+
+use crate::errors::{CryptoError, Result};
+use crate::utils::hash_keccak256;
+use cryptography_types::stealth::OutputNote;
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint, scalar::Scalar,
+};
+use sha2::{Digest, Sha512};
+
+/// Scalar added to the recipient's ring key for this output:
+/// `P = ring_pubkey + offset * G`, spendable with `ring_secret + offset`.
+pub fn output_key_offset(shared_secret: &[u8; 32]) -> Scalar {
+    let mut hasher = Sha512::new();
+    hasher.update(b"GELAP_OUTPUT_KEY_V1");
+    hasher.update(shared_secret);
+    let hash = hasher.finalize();
+
+    Scalar::from_bytes_mod_order_wide(&hash.into())
+}
+
+pub fn output_ring_pubkey(
+    ring_pubkey: &RistrettoPoint,
+    shared_secret: &[u8; 32],
+) -> RistrettoPoint {
+    ring_pubkey + output_key_offset(shared_secret) * RISTRETTO_BASEPOINT_POINT
+}
+
+pub fn encrypt_note(
+    shared_secret: &[u8; 32],
+    ring_pubkey: &RistrettoPoint,
+    amount: u64,
+    blinding: &Scalar,
+) -> OutputNote {
+    let (amount_key, blinding_key) = note_keys(shared_secret);
+
+    let mut encrypted_amount = amount.to_le_bytes();
+    xor(&mut encrypted_amount, &amount_key[..8]);
+
+    let mut encrypted_blinding = blinding.to_bytes();
+    xor(&mut encrypted_blinding, &blinding_key);
+
+    OutputNote {
+        ring_pubkey: ring_pubkey.compress().to_bytes(),
+        encrypted_amount,
+        encrypted_blinding,
+    }
+}
+
+/// Recovers `(amount, blinding)`. A wrong shared secret yields garbage, so
+/// callers should check the result against the output commitment.
+pub fn decrypt_note(shared_secret: &[u8; 32], note: &OutputNote) -> Result<(u64, Scalar)> {
+    let (amount_key, blinding_key) = note_keys(shared_secret);
+
+    let mut amount = note.encrypted_amount;
+    xor(&mut amount, &amount_key[..8]);
+
+    let mut blinding = note.encrypted_blinding;
+    xor(&mut blinding, &blinding_key);
+    let blinding =
+        Option::from(Scalar::from_canonical_bytes(blinding)).ok_or(CryptoError::InvalidScalar)?;
+
+    Ok((u64::from_le_bytes(amount), blinding))
+}
+
+fn note_keys(shared_secret: &[u8; 32]) -> ([u8; 32], [u8; 32]) {
+    let mut amount_input = b"GELAP_NOTE_AMOUNT_V1".to_vec();
+    amount_input.extend_from_slice(shared_secret);
+
+    let mut blinding_input = b"GELAP_NOTE_BLINDING_V1".to_vec();
+    blinding_input.extend_from_slice(shared_secret);
+
+    (
+        hash_keccak256(&amount_input),
+        hash_keccak256(&blinding_input),
+    )
+}
+
+fn xor(data: &mut [u8], key: &[u8]) {
+    for (byte, k) in data.iter_mut().zip(key) {
+        *byte ^= k;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pedersen::generate_blinding;
+
+    #[test]
+    fn test_note_roundtrip() {
+        let shared_secret = [7u8; 32];
+        let ring_pubkey = output_ring_pubkey(&RISTRETTO_BASEPOINT_POINT, &shared_secret);
+        let blinding = generate_blinding();
+
+        let note = encrypt_note(&shared_secret, &ring_pubkey, 1_000, &blinding);
+        assert_eq!(note.ring_pubkey, ring_pubkey.compress().to_bytes());
+        assert_ne!(note.encrypted_blinding, blinding.to_bytes());
+
+        let (amount, decrypted) = decrypt_note(&shared_secret, &note).unwrap();
+        assert_eq!(amount, 1_000);
+        assert_eq!(decrypted, blinding);
+
+        let wrong = decrypt_note(&[8u8; 32], &note);
+        assert!(!matches!(wrong, Ok((1_000, b)) if b == blinding));
+    }
+
+    #[test]
+    fn test_output_key_offset() {
+        let ring_secret = generate_blinding();
+        let ring_pubkey = ring_secret * RISTRETTO_BASEPOINT_POINT;
+        let shared_secret = [9u8; 32];
+
+        let one_time_secret = ring_secret + output_key_offset(&shared_secret);
+        assert_eq!(
+            one_time_secret * RISTRETTO_BASEPOINT_POINT,
+            output_ring_pubkey(&ring_pubkey, &shared_secret)
+        );
+    }
+}
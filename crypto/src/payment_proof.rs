@@ -0,0 +1,324 @@
+// Payment proofs: a sender shows a merchant or arbiter that a stealth output
+// was derived for a given recipient, using the ephemeral secret r returned by
+// `generate_stealth_eth`.
+//
+// The proof reveals the ECDH point S = r*V for the recipient's view key V and
+// a Chaum-Pedersen DLEQ that log_G(R) = log_V(S), where R = r*G is the
+// ephemeral key published with the output. The verifier then recomputes the
+// stealth address from S and the recipient's spend key. Knowing S opens the
+// output's note, so the proof also discloses the amount of that one output.
+//
+// Stealth keys live on secp256k1, so the DLEQ is done here rather than with
+// the Ristretto proofs in `sigma`; the challenge uses the same Merlin transcript.
+
+use crate::errors::{CryptoError, Result};
+use crate::ethereum::{hash_shared_secret, stealth_address_from_shared, StealthAddressEth};
+use crate::keys::StealthMetaAddress;
+use crate::note::{decrypt_note, output_ring_pubkey};
+use crate::pedersen::PedersenCommitment;
+use cryptography_types::stealth::OutputNote;
+use merlin::Transcript;
+use rand::thread_rng;
+use secp256k1::{PublicKey, Scalar as SecpScalar, Secp256k1, SecretKey};
+use serde::{Deserialize, Serialize};
+
+/// Shows that a stealth output pays a recipient's meta-address.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PaymentProof {
+    /// ECDH point `S = r*V`.
+    pub shared_point: PublicKey,
+    /// Nonce commitment `k*G`.
+    pub nonce_g: PublicKey,
+    /// Nonce commitment `k*V`.
+    pub nonce_v: PublicKey,
+    /// `k + e*r`, big-endian.
+    #[cfg_attr(feature = "hex", serde(with = "cryptography_types::serde_hex"))]
+    pub response: [u8; 32],
+}
+
+impl PaymentProof {
+    /// shared point (33) || nonce_g (33) || nonce_v (33) || response (32)
+    pub const SIZE: usize = 131;
+
+    /// `ephemeral_secret` is the secret returned by `generate_stealth_eth`
+    /// alongside `output`.
+    pub fn prove(
+        ephemeral_secret: &SecretKey,
+        output: &StealthAddressEth,
+        recipient: &StealthMetaAddress,
+        context: &[u8],
+    ) -> Result<Self> {
+        let secp = Secp256k1::new();
+        let ephemeral_pubkey = PublicKey::from_secret_key(&secp, ephemeral_secret);
+        if ephemeral_pubkey.serialize()[..] != output.ephemeral_pubkey[..] {
+            return Err(CryptoError::InvalidInput(
+                "Ephemeral secret does not match the output".into(),
+            ));
+        }
+
+        let shared_point = recipient
+            .view_pubkey
+            .mul_tweak(&secp, &(*ephemeral_secret).into())
+            .map_err(|_| CryptoError::EcdhFailed)?;
+        let (address, _) =
+            stealth_address_from_shared(&secp, &shared_point, &recipient.spend_pubkey)?;
+        if address != output.stealth_address {
+            return Err(CryptoError::InvalidInput(
+                "Output was not derived for this recipient".into(),
+            ));
+        }
+
+        let mut k = SecretKey::new(&mut thread_rng());
+        let nonce_g = PublicKey::from_secret_key(&secp, &k);
+        let nonce_v = recipient
+            .view_pubkey
+            .mul_tweak(&secp, &k.into())
+            .map_err(|_| CryptoError::EcdhFailed)?;
+
+        let challenge = payment_challenge(
+            output,
+            &ephemeral_pubkey,
+            &recipient.view_pubkey,
+            &shared_point,
+            &nonce_g,
+            &nonce_v,
+            context,
+        )?;
+        let response = ephemeral_secret
+            .mul_tweak(&challenge)
+            .and_then(|er| k.add_tweak(&er.into()))
+            .map_err(|_| CryptoError::InvalidScalar)?;
+        k.non_secure_erase();
+
+        Ok(Self {
+            shared_point,
+            nonce_g,
+            nonce_v,
+            response: response.secret_bytes(),
+        })
+    }
+
+    /// Checks the DLEQ against the output's ephemeral key and the recipient's
+    /// view key, then that `S` and the spend key give the output's address.
+    pub fn verify(
+        &self,
+        output: &StealthAddressEth,
+        recipient: &StealthMetaAddress,
+        context: &[u8],
+    ) -> Result<()> {
+        let secp = Secp256k1::new();
+        let ephemeral_pubkey = PublicKey::from_slice(&output.ephemeral_pubkey)
+            .map_err(|_| CryptoError::InvalidPublicKey)?;
+
+        let challenge = payment_challenge(
+            output,
+            &ephemeral_pubkey,
+            &recipient.view_pubkey,
+            &self.shared_point,
+            &self.nonce_g,
+            &self.nonce_v,
+            context,
+        )?;
+        let response =
+            SecretKey::from_slice(&self.response).map_err(|_| CryptoError::InvalidScalar)?;
+
+        // z*G == A1 + e*R and z*V == A2 + e*S
+        let lhs_g = PublicKey::from_secret_key(&secp, &response);
+        let lhs_v = recipient
+            .view_pubkey
+            .mul_tweak(&secp, &response.into())
+            .map_err(|_| CryptoError::EcdhFailed)?;
+        let rhs_g = ephemeral_pubkey
+            .mul_tweak(&secp, &challenge)
+            .and_then(|p| p.combine(&self.nonce_g));
+        let rhs_v = self
+            .shared_point
+            .mul_tweak(&secp, &challenge)
+            .and_then(|p| p.combine(&self.nonce_v));
+
+        if rhs_g != Ok(lhs_g) || rhs_v != Ok(lhs_v) {
+            return Err(CryptoError::ProofVerificationFailed);
+        }
+        let (address, _) =
+            stealth_address_from_shared(&secp, &self.shared_point, &recipient.spend_pubkey)?;
+        if address != output.stealth_address {
+            return Err(CryptoError::ProofVerificationFailed);
+        }
+        Ok(())
+    }
+
+    /// The stealth shared secret of the output, as used by the note functions.
+    /// Only meaningful once [`PaymentProof::verify`] has passed.
+    pub fn shared_secret(&self) -> [u8; 32] {
+        hash_shared_secret(&self.shared_point)
+    }
+
+    /// Opens the output's note and returns the amount paid. Checks that the
+    /// note's ring key belongs to the recipient and that the note opens the
+    /// output commitment. Call after [`PaymentProof::verify`].
+    pub fn verify_amount(
+        &self,
+        recipient: &StealthMetaAddress,
+        commitment: &PedersenCommitment,
+        note: &OutputNote,
+    ) -> Result<u64> {
+        let shared_secret = self.shared_secret();
+
+        let ring_pubkey = output_ring_pubkey(&recipient.ring_pubkey, &shared_secret);
+        if ring_pubkey.compress().to_bytes() != note.ring_pubkey {
+            return Err(CryptoError::ProofVerificationFailed);
+        }
+
+        let (amount, blinding) = decrypt_note(&shared_secret, note)?;
+        if !commitment.verify(amount, &blinding) {
+            return Err(CryptoError::CommitmentVerificationFailed);
+        }
+        Ok(amount)
+    }
+
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0u8; Self::SIZE];
+        bytes[..33].copy_from_slice(&self.shared_point.serialize());
+        bytes[33..66].copy_from_slice(&self.nonce_g.serialize());
+        bytes[66..99].copy_from_slice(&self.nonce_v.serialize());
+        bytes[99..].copy_from_slice(&self.response);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != Self::SIZE {
+            return Err(CryptoError::Deserialization(format!(
+                "Payment proof must be {} bytes, got {}",
+                Self::SIZE,
+                bytes.len()
+            )));
+        }
+
+        let point = |b: &[u8]| PublicKey::from_slice(b).map_err(|_| CryptoError::InvalidPublicKey);
+        Ok(Self {
+            shared_point: point(&bytes[..33])?,
+            nonce_g: point(&bytes[33..66])?,
+            nonce_v: point(&bytes[66..99])?,
+            response: bytes[99..].try_into().expect("32 bytes"),
+        })
+    }
+}
+
+fn payment_challenge(
+    output: &StealthAddressEth,
+    ephemeral_pubkey: &PublicKey,
+    view_pubkey: &PublicKey,
+    shared_point: &PublicKey,
+    nonce_g: &PublicKey,
+    nonce_v: &PublicKey,
+    context: &[u8],
+) -> Result<SecpScalar> {
+    let mut transcript = Transcript::new(b"GELAP_PAYMENT_PROOF_V1");
+    transcript.append_message(b"context", context);
+    transcript.append_message(b"stealth_address", &output.stealth_address);
+    transcript.append_message(b"R", &ephemeral_pubkey.serialize());
+    transcript.append_message(b"V", &view_pubkey.serialize());
+    transcript.append_message(b"S", &shared_point.serialize());
+    transcript.append_message(b"A_G", &nonce_g.serialize());
+    transcript.append_message(b"A_V", &nonce_v.serialize());
+
+    let mut challenge = [0u8; 32];
+    transcript.challenge_bytes(b"e", &mut challenge);
+    SecpScalar::from_be_bytes(challenge).map_err(|_| CryptoError::InvalidScalar)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ethereum::generate_stealth_eth;
+    use crate::ethereum::stealth_shared_secret;
+    use crate::keys::StealthKeys;
+    use crate::note::encrypt_note;
+    use crate::pedersen::generate_blinding;
+
+    fn pay(recipient: &StealthMetaAddress) -> (StealthAddressEth, SecretKey) {
+        generate_stealth_eth(&recipient.view_pubkey, &recipient.spend_pubkey).unwrap()
+    }
+
+    #[test]
+    fn test_payment_proof() {
+        let recipient = StealthKeys::from_seed(&[1u8; 64], 0)
+            .unwrap()
+            .meta_address();
+        let other = StealthKeys::from_seed(&[2u8; 64], 0)
+            .unwrap()
+            .meta_address();
+        let (output, ephemeral_secret) = pay(&recipient);
+
+        let proof =
+            PaymentProof::prove(&ephemeral_secret, &output, &recipient, b"order-7").unwrap();
+        assert!(proof.verify(&output, &recipient, b"order-7").is_ok());
+        assert!(proof.verify(&output, &recipient, b"order-8").is_err());
+        assert!(proof.verify(&output, &other, b"order-7").is_err());
+
+        let (unrelated, _) = pay(&recipient);
+        assert!(proof.verify(&unrelated, &recipient, b"order-7").is_err());
+
+        assert!(PaymentProof::prove(&ephemeral_secret, &output, &other, b"").is_err());
+        let (_, wrong_secret) = pay(&recipient);
+        assert!(PaymentProof::prove(&wrong_secret, &output, &recipient, b"").is_err());
+    }
+
+    #[test]
+    fn test_forged_shared_point_fails() {
+        let recipient = StealthKeys::from_seed(&[1u8; 64], 0)
+            .unwrap()
+            .meta_address();
+        let (output, ephemeral_secret) = pay(&recipient);
+        let mut proof = PaymentProof::prove(&ephemeral_secret, &output, &recipient, b"").unwrap();
+
+        // The correct S paired with a DLEQ for another output must not pass
+        let (other_output, other_secret) = pay(&recipient);
+        let other_proof =
+            PaymentProof::prove(&other_secret, &other_output, &recipient, b"").unwrap();
+        proof.shared_point = other_proof.shared_point;
+        assert!(proof.verify(&output, &recipient, b"").is_err());
+    }
+
+    #[test]
+    fn test_verify_amount() {
+        let keys = StealthKeys::from_seed(&[3u8; 64], 0).unwrap();
+        let recipient = keys.meta_address();
+        let (output, ephemeral_secret) = pay(&recipient);
+
+        let shared_secret =
+            stealth_shared_secret(&recipient.view_pubkey, &ephemeral_secret).unwrap();
+        let blinding = generate_blinding();
+        let commitment = PedersenCommitment::new(250, &blinding);
+        let ring_pubkey = output_ring_pubkey(&recipient.ring_pubkey, &shared_secret);
+        let note = encrypt_note(&shared_secret, &ring_pubkey, 250, &blinding);
+
+        let proof = PaymentProof::prove(&ephemeral_secret, &output, &recipient, b"").unwrap();
+        proof.verify(&output, &recipient, b"").unwrap();
+        assert_eq!(
+            proof.verify_amount(&recipient, &commitment, &note).unwrap(),
+            250
+        );
+
+        let other = PedersenCommitment::new(251, &blinding);
+        assert!(proof.verify_amount(&recipient, &other, &note).is_err());
+    }
+
+    #[test]
+    fn test_payment_proof_bytes() {
+        let recipient = StealthKeys::from_seed(&[4u8; 64], 0)
+            .unwrap()
+            .meta_address();
+        let (output, ephemeral_secret) = pay(&recipient);
+        let proof = PaymentProof::prove(&ephemeral_secret, &output, &recipient, b"").unwrap();
+
+        let decoded = PaymentProof::from_bytes(&proof.to_bytes()).unwrap();
+        assert_eq!(decoded, proof);
+        assert!(decoded.verify(&output, &recipient, b"").is_ok());
+        assert!(PaymentProof::from_bytes(&proof.to_bytes()[1..]).is_err());
+
+        let json = serde_json::to_string(&proof).unwrap();
+        let decoded: PaymentProof = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, proof);
+    }
+}
@@ -5,6 +5,7 @@ use curve25519_dalek::{
 };
 
 use crate::errors::{CryptoError, Result};
+use cryptography_types::{commitment::CommitmentData, domain::PEDERSEN_H_DOMAIN};
 use rand::RngCore;
 use rand_core::OsRng;
 use serde::{Deserialize, Serialize};
@@ -12,6 +13,7 @@ use sha2::{Digest, Sha512};
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub struct PedersenCommitment {
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex::point"))]
     pub point: RistrettoPoint,
 }
 
@@ -55,12 +57,26 @@ impl PedersenCommitment {
     }
 }
 
+impl From<PedersenCommitment> for CommitmentData {
+    fn from(commitment: PedersenCommitment) -> Self {
+        CommitmentData::new(commitment.to_bytes())
+    }
+}
+
+impl TryFrom<CommitmentData> for PedersenCommitment {
+    type Error = CryptoError;
+
+    fn try_from(data: CommitmentData) -> Result<Self> {
+        Self::from_bytes(&data.commitment)
+    }
+}
+
 pub fn get_h_generator() -> RistrettoPoint {
     let g = RISTRETTO_BASEPOINT_POINT;
     let g_bytes = g.compress().to_bytes();
 
     let mut hasher = Sha512::new();
-    hasher.update(b"PEDERSEN_H_GENERATOR_V1");
+    hasher.update(PEDERSEN_H_DOMAIN);
     hasher.update(g_bytes);
     let hash = hasher.finalize();
 
@@ -85,6 +101,16 @@ pub fn generate_blinding() -> Scalar {
 mod tests {
     use super::*;
 
+    // Pinned so a change to the derivation, which breaks every existing
+    // commitment, cannot go unnoticed
+    #[test]
+    fn test_h_generator_vector() {
+        assert_eq!(
+            hex::encode(get_h_generator().compress().to_bytes()),
+            "76b68e493c6b450a44d226285f65b3002d66458692b75ecff47e281cb0a81a1d"
+        );
+    }
+
     #[test]
     fn test_pedersen_commitment_basic() {
         let amount = 100u64;
@@ -113,6 +139,18 @@ mod tests {
         assert_eq!(commitment, recovered)
     }
 
+    #[test]
+    fn test_commitment_data_conversion() {
+        let commitment = PedersenCommitment::new(7, &generate_blinding());
+
+        let data = CommitmentData::from(commitment);
+        assert_eq!(data.commitment, commitment.to_bytes());
+        assert_eq!(PedersenCommitment::try_from(data).unwrap(), commitment);
+
+        let invalid = CommitmentData::new([0xff; 32]);
+        assert!(PedersenCommitment::try_from(invalid).is_err());
+    }
+
     #[test]
     fn test_commitment_homomorphic_addition() {
         let amount1 = 50u64;
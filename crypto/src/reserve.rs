@@ -0,0 +1,508 @@
+// Reserve proofs: a wallet shows it controls unspent outputs worth at least
+// some minimum without revealing which outputs they are.
+//
+// Each owned output is hidden in a ring of on-chain outputs and re-committed
+// to a fresh pseudo-commitment C' with the same amount. A two-column MLSAG per
+// input proves knowledge of the one-time ring secret x of some member P_i
+// (linked through the key image I = x*Hp(P_i)) and of z with C_i - C' = z*H,
+// so C' holds that member's amount. A range proof on the sum of the
+// pseudo-commitments then shows the total is at least the minimum.
+//
+// Key images are revealed so the verifier can check them against the spent
+// set. As a consequence, later spends of the proven outputs are linkable to
+// the proof.
+
+use crate::disclosure::MinimumAmountProof;
+use crate::errors::{CryptoError, Result};
+use crate::pedersen::{generate_blinding, get_h_generator, PedersenCommitment};
+use crate::ring_signature::{compute_key_image, hash_to_point};
+use crate::sigma::TranscriptProtocol;
+use crate::utils::{canonical_scalar, decompress_point};
+use cryptography_types::wallet::OwnedOutput;
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint, scalar::Scalar,
+};
+use merlin::Transcript;
+use rand::{seq::SliceRandom, thread_rng, Rng};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use zeroize::Zeroize;
+
+/// Largest ring a proof may use. Verification work grows with the ring, so a
+/// verifier must not accept arbitrarily large ones.
+pub const MAX_RING_SIZE: usize = 128;
+
+/// An on-chain output as it appears in a reserve proof ring.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct RingMember {
+    #[cfg_attr(feature = "hex", serde(with = "cryptography_types::serde_hex"))]
+    pub ring_pubkey: [u8; 32],
+    #[cfg_attr(feature = "hex", serde(with = "cryptography_types::serde_hex"))]
+    pub commitment: [u8; 32],
+}
+
+impl From<&OwnedOutput> for RingMember {
+    fn from(output: &OwnedOutput) -> Self {
+        Self {
+            ring_pubkey: output.ring_pubkey,
+            commitment: output.commitment,
+        }
+    }
+}
+
+/// One owned output, hidden among `ring`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReserveInput {
+    pub ring: Vec<RingMember>,
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex::point"))]
+    pub pseudo_commitment: RistrettoPoint,
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex::point"))]
+    pub key_image: RistrettoPoint,
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex::scalar"))]
+    pub c0: Scalar,
+    /// Responses for the ring key column.
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex::scalars"))]
+    pub r_key: Vec<Scalar>,
+    /// Responses for the commitment column.
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex::scalars"))]
+    pub r_commitment: Vec<Scalar>,
+}
+
+/// Shows unspent outputs worth at least [`ReserveProof::minimum`].
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ReserveProof {
+    pub inputs: Vec<ReserveInput>,
+    /// Range proof on the sum of the pseudo-commitments.
+    pub total: MinimumAmountProof,
+}
+
+impl ReserveProof {
+    /// Proves that `outputs` hold at least `minimum` in total. Each output
+    /// gets a ring of `ring_size` members, the rest drawn from `decoys`.
+    pub fn prove<'a, I>(
+        ring_secret: &Scalar,
+        outputs: I,
+        decoys: &[RingMember],
+        ring_size: usize,
+        minimum: u64,
+        context: &[u8],
+    ) -> Result<Self>
+    where
+        I: IntoIterator<Item = &'a OwnedOutput>,
+    {
+        let outputs: Vec<&OwnedOutput> = outputs.into_iter().collect();
+        if outputs.is_empty() {
+            return Err(CryptoError::InvalidInput(
+                "Reserve proof has no outputs".into(),
+            ));
+        }
+        if ring_size == 0 || ring_size > MAX_RING_SIZE {
+            return Err(CryptoError::InvalidInput(format!(
+                "Ring size must be between 1 and {}",
+                MAX_RING_SIZE
+            )));
+        }
+
+        let mut rng = thread_rng();
+
+        let owned_keys: Vec<[u8; 32]> = outputs.iter().map(|o| o.ring_pubkey).collect();
+        let mut pool: Vec<RingMember> = Vec::new();
+        for decoy in decoys {
+            if !owned_keys.contains(&decoy.ring_pubkey) && !pool.contains(decoy) {
+                pool.push(*decoy);
+            }
+        }
+
+        let per_ring = ring_size - 1;
+        let needed = per_ring * outputs.len();
+        if pool.len() < needed {
+            return Err(CryptoError::InsufficientDecoys {
+                needed,
+                available: pool.len(),
+            });
+        }
+        pool.shuffle(&mut rng);
+
+        let h = get_h_generator();
+        let mut total: u64 = 0;
+        let mut blinding_sum = Scalar::ZERO;
+        let mut inputs = Vec::new();
+        let mut signers = Vec::new();
+
+        for (i, output) in outputs.iter().enumerate() {
+            if !output.is_unspent() {
+                return Err(CryptoError::InvalidInput("Output is already spent".into()));
+            }
+
+            let blinding = canonical_scalar(&output.blinding)?;
+            let commitment = PedersenCommitment::from_bytes(&output.commitment)?;
+            if !commitment.verify(output.amount, &blinding) {
+                return Err(CryptoError::CommitmentVerificationFailed);
+            }
+
+            let secret_key = ring_secret + canonical_scalar(&output.ring_key_offset)?;
+            let public_key = decompress_point(&output.ring_pubkey)?;
+            if secret_key * RISTRETTO_BASEPOINT_POINT != public_key {
+                return Err(CryptoError::InvalidInput(
+                    "Output is not spendable with this key".into(),
+                ));
+            }
+
+            total = total
+                .checked_add(output.amount)
+                .ok_or_else(|| CryptoError::InvalidInput("Reserve total overflows u64".into()))?;
+
+            let pseudo_blinding = generate_blinding();
+            blinding_sum += pseudo_blinding;
+
+            let secret_index = rng.gen_range(0..ring_size);
+            let mut ring = pool[i * per_ring..(i + 1) * per_ring].to_vec();
+            ring.insert(secret_index, RingMember::from(*output));
+
+            inputs.push(ReserveInput {
+                ring,
+                pseudo_commitment: PedersenCommitment::new(output.amount, &pseudo_blinding).point,
+                key_image: compute_key_image(&secret_key, &public_key),
+                c0: Scalar::ZERO,
+                r_key: Vec::new(),
+                r_commitment: Vec::new(),
+            });
+            signers.push((secret_index, secret_key, blinding - pseudo_blinding));
+        }
+
+        let message = reserve_message(&inputs, minimum, context);
+
+        for (input, (secret_index, mut secret_key, mut blinding_diff)) in
+            inputs.iter_mut().zip(signers)
+        {
+            let ring = ring_points(input)?;
+            sign_input(
+                input,
+                &ring,
+                &message,
+                secret_index,
+                &secret_key,
+                &blinding_diff,
+                &h,
+            );
+            secret_key.zeroize();
+            blinding_diff.zeroize();
+        }
+
+        let summed = PedersenCommitment {
+            point: inputs.iter().map(|input| input.pseudo_commitment).sum(),
+        };
+        let total = MinimumAmountProof::prove(&summed, total, &blinding_sum, minimum, &message)?;
+        blinding_sum.zeroize();
+
+        Ok(Self { inputs, total })
+    }
+
+    pub fn minimum(&self) -> u64 {
+        self.total.minimum
+    }
+
+    /// Checks every ring member against `outputs` (the on-chain outputs) and
+    /// every key image against `spent_key_images`, then the ring signatures
+    /// and the range proof on the total.
+    pub fn verify(
+        &self,
+        outputs: &HashSet<RingMember>,
+        spent_key_images: &HashSet<[u8; 32]>,
+        context: &[u8],
+    ) -> Result<()> {
+        if self.inputs.is_empty() {
+            return Err(CryptoError::ProofVerificationFailed);
+        }
+
+        let mut key_images = HashSet::new();
+        for input in &self.inputs {
+            if input.ring.len() > MAX_RING_SIZE {
+                return Err(CryptoError::InvalidInput(format!(
+                    "Ring of {} members exceeds the maximum of {}",
+                    input.ring.len(),
+                    MAX_RING_SIZE
+                )));
+            }
+            if let Some(member) = input.ring.iter().find(|m| !outputs.contains(m)) {
+                return Err(CryptoError::InvalidInput(format!(
+                    "Ring member 0x{} is not an on-chain output",
+                    hex::encode(member.ring_pubkey)
+                )));
+            }
+
+            let key_image = input.key_image.compress().to_bytes();
+            if spent_key_images.contains(&key_image) || !key_images.insert(key_image) {
+                return Err(CryptoError::KeyImageUsed);
+            }
+        }
+
+        let h = get_h_generator();
+        let message = reserve_message(&self.inputs, self.minimum(), context);
+        for input in &self.inputs {
+            let ring = ring_points(input)?;
+            if !verify_input(input, &ring, &message, &h) {
+                return Err(CryptoError::ProofVerificationFailed);
+            }
+        }
+
+        let summed = PedersenCommitment {
+            point: self
+                .inputs
+                .iter()
+                .map(|input| input.pseudo_commitment)
+                .sum(),
+        };
+        self.total.verify(&summed, &message)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::serialize(self).expect("Serialization should not fail")
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        bincode::deserialize(bytes).map_err(|e| CryptoError::Deserialization(e.to_string()))
+    }
+}
+
+/// `(P_i, C_i - C')` for every ring member.
+fn ring_points(input: &ReserveInput) -> Result<Vec<(RistrettoPoint, RistrettoPoint)>> {
+    input
+        .ring
+        .iter()
+        .map(|member| {
+            let key = decompress_point(&member.ring_pubkey)?;
+            let commitment = decompress_point(&member.commitment)?;
+            Ok((key, commitment - input.pseudo_commitment))
+        })
+        .collect()
+}
+
+fn sign_input(
+    input: &mut ReserveInput,
+    ring: &[(RistrettoPoint, RistrettoPoint)],
+    message: &[u8; 64],
+    secret_index: usize,
+    secret_key: &Scalar,
+    blinding_diff: &Scalar,
+    h: &RistrettoPoint,
+) {
+    let n = ring.len();
+    let mut c = vec![Scalar::ZERO; n];
+    let mut r_key = vec![Scalar::ZERO; n];
+    let mut r_commitment = vec![Scalar::ZERO; n];
+
+    let mut alpha_key = generate_blinding();
+    let mut alpha_commitment = generate_blinding();
+
+    let start = (secret_index + 1) % n;
+    c[start] = mlsag_challenge(
+        message,
+        &(alpha_key * RISTRETTO_BASEPOINT_POINT),
+        &(alpha_key * hash_to_point(&ring[secret_index].0)),
+        &(alpha_commitment * h),
+    );
+
+    for i in 0..(n - 1) {
+        let idx = (start + i) % n;
+        let (key, diff) = &ring[idx];
+
+        r_key[idx] = generate_blinding();
+        r_commitment[idx] = generate_blinding();
+
+        c[(idx + 1) % n] = mlsag_challenge(
+            message,
+            &(r_key[idx] * RISTRETTO_BASEPOINT_POINT + c[idx] * key),
+            &(r_key[idx] * hash_to_point(key) + c[idx] * input.key_image),
+            &(r_commitment[idx] * h + c[idx] * diff),
+        );
+    }
+
+    r_key[secret_index] = alpha_key - c[secret_index] * secret_key;
+    r_commitment[secret_index] = alpha_commitment - c[secret_index] * blinding_diff;
+    alpha_key.zeroize();
+    alpha_commitment.zeroize();
+
+    input.c0 = c[0];
+    input.r_key = r_key;
+    input.r_commitment = r_commitment;
+}
+
+fn verify_input(
+    input: &ReserveInput,
+    ring: &[(RistrettoPoint, RistrettoPoint)],
+    message: &[u8; 64],
+    h: &RistrettoPoint,
+) -> bool {
+    let n = ring.len();
+    if n == 0 || input.r_key.len() != n || input.r_commitment.len() != n {
+        return false;
+    }
+
+    let mut c = input.c0;
+    for (i, (key, diff)) in ring.iter().enumerate() {
+        c = mlsag_challenge(
+            message,
+            &(input.r_key[i] * RISTRETTO_BASEPOINT_POINT + c * key),
+            &(input.r_key[i] * hash_to_point(key) + c * input.key_image),
+            &(input.r_commitment[i] * h + c * diff),
+        );
+    }
+    c == input.c0
+}
+
+/// Binds the context, the minimum and every ring, pseudo-commitment and key
+/// image, so no part of the proof can be swapped out.
+fn reserve_message(inputs: &[ReserveInput], minimum: u64, context: &[u8]) -> [u8; 64] {
+    let mut transcript = Transcript::new(b"GELAP_RESERVE_PROOF_V1");
+    transcript.append_message(b"context", context);
+    transcript.append_u64(b"minimum", minimum);
+    transcript.append_u64(b"inputs", inputs.len() as u64);
+    for input in inputs {
+        transcript.append_u64(b"ring_size", input.ring.len() as u64);
+        for member in &input.ring {
+            transcript.append_message(b"ring_pubkey", &member.ring_pubkey);
+            transcript.append_message(b"commitment", &member.commitment);
+        }
+        transcript.append_point(b"pseudo_commitment", &input.pseudo_commitment);
+        transcript.append_point(b"key_image", &input.key_image);
+    }
+
+    let mut message = [0u8; 64];
+    transcript.challenge_bytes(b"message", &mut message);
+    message
+}
+
+fn mlsag_challenge(
+    message: &[u8; 64],
+    l_key: &RistrettoPoint,
+    r_key: &RistrettoPoint,
+    l_commitment: &RistrettoPoint,
+) -> Scalar {
+    let mut transcript = Transcript::new(b"GELAP_RESERVE_MLSAG_V1");
+    transcript.append_message(b"message", message);
+    transcript.append_point(b"L_key", l_key);
+    transcript.append_point(b"R_key", r_key);
+    transcript.append_point(b"L_commitment", l_commitment);
+    transcript.challenge_scalar(b"c")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::StealthKeys;
+    use crate::test_utils::{decoy_members, owned_output};
+
+    fn chain(owned: &[OwnedOutput], decoys: &[RingMember]) -> HashSet<RingMember> {
+        owned
+            .iter()
+            .map(RingMember::from)
+            .chain(decoys.iter().copied())
+            .collect()
+    }
+
+    #[test]
+    fn test_reserve_proof() {
+        let keys = StealthKeys::from_seed(&[1u8; 64], 0).unwrap();
+        let owned = vec![owned_output(&keys, 600), owned_output(&keys, 500)];
+        let decoys = decoy_members(10);
+        let chain = chain(&owned, &decoys);
+        let spent = HashSet::new();
+
+        let proof =
+            ReserveProof::prove(keys.ring_secret.expose(), &owned, &decoys, 4, 1_000, b"q3")
+                .unwrap();
+        assert_eq!(proof.minimum(), 1_000);
+        assert!(proof.inputs.iter().all(|input| input.ring.len() == 4));
+        assert!(proof.verify(&chain, &spent, b"q3").is_ok());
+        assert!(proof.verify(&chain, &spent, b"q4").is_err());
+
+        assert!(
+            ReserveProof::prove(keys.ring_secret.expose(), &owned, &decoys, 4, 1_101, b"q3")
+                .is_err()
+        );
+        assert!(
+            ReserveProof::prove(keys.ring_secret.expose(), &owned, &decoys, 7, 1_000, b"q3")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_reserve_proof_rejects_spent_and_unknown_outputs() {
+        let keys = StealthKeys::from_seed(&[2u8; 64], 0).unwrap();
+        let owned = vec![owned_output(&keys, 100)];
+        let decoys = decoy_members(5);
+        let proof =
+            ReserveProof::prove(keys.ring_secret.expose(), &owned, &decoys, 3, 100, b"").unwrap();
+
+        let spent: HashSet<[u8; 32]> = [owned[0].key_image].into();
+        assert!(matches!(
+            proof.verify(&chain(&owned, &decoys), &spent, b""),
+            Err(CryptoError::KeyImageUsed)
+        ));
+
+        assert!(proof
+            .verify(&chain(&[], &decoys), &HashSet::new(), b"")
+            .is_err());
+
+        let other = StealthKeys::from_seed(&[3u8; 64], 0).unwrap();
+        assert!(
+            ReserveProof::prove(other.ring_secret.expose(), &owned, &decoys, 3, 100, b"").is_err()
+        );
+    }
+
+    #[test]
+    fn test_reserve_proof_rejects_tampering() {
+        let keys = StealthKeys::from_seed(&[4u8; 64], 0).unwrap();
+        let owned = vec![owned_output(&keys, 300), owned_output(&keys, 200)];
+        let decoys = decoy_members(6);
+        let chain = chain(&owned, &decoys);
+        let spent = HashSet::new();
+        let proof =
+            ReserveProof::prove(keys.ring_secret.expose(), &owned, &decoys, 3, 400, b"").unwrap();
+
+        // Counting the same output twice
+        let mut doubled = proof.clone();
+        doubled.inputs[1] = doubled.inputs[0].clone();
+        assert!(doubled.verify(&chain, &spent, b"").is_err());
+
+        // Inflating one pseudo-commitment breaks its ring signature
+        let mut inflated = proof.clone();
+        inflated.inputs[0].pseudo_commitment += RISTRETTO_BASEPOINT_POINT;
+        assert!(inflated.verify(&chain, &spent, b"").is_err());
+
+        let decoded = ReserveProof::from_bytes(&proof.to_bytes()).unwrap();
+        assert!(decoded.verify(&chain, &spent, b"").is_ok());
+
+        let json = serde_json::to_string(&proof).unwrap();
+        let decoded: ReserveProof = serde_json::from_str(&json).unwrap();
+        assert!(decoded.verify(&chain, &spent, b"").is_ok());
+    }
+
+    #[test]
+    fn test_ring_size_bounded() {
+        let keys = StealthKeys::from_seed(&[5u8; 64], 0).unwrap();
+        let owned = vec![owned_output(&keys, 100)];
+        let decoys = decoy_members(MAX_RING_SIZE + 1);
+        let chain = chain(&owned, &decoys);
+
+        let result = ReserveProof::prove(
+            keys.ring_secret.expose(),
+            &owned,
+            &decoys,
+            MAX_RING_SIZE + 1,
+            100,
+            b"",
+        );
+        assert!(matches!(result, Err(CryptoError::InvalidInput(_))));
+
+        // Every member is on chain, yet the oversized ring is refused
+        let mut oversized =
+            ReserveProof::prove(keys.ring_secret.expose(), &owned, &decoys, 3, 100, b"").unwrap();
+        oversized.inputs[0].ring = decoys;
+        assert!(matches!(
+            oversized.verify(&chain, &HashSet::new(), b""),
+            Err(CryptoError::InvalidInput(_))
+        ));
+    }
+}
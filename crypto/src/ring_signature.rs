@@ -1,16 +1,27 @@
 use crate::errors::{CryptoError, Result};
+use crate::sigma::{DleqProof, DleqStatement};
+use crate::utils::{canonical_scalar, decompress_point};
+use cryptography_types::{
+    domain::{HASH_TO_POINT_DOMAIN, RING_SIG_DOMAIN},
+    signature::RingSignatureData,
+};
 use curve25519_dalek::{
     constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint, scalar::Scalar,
 };
+use merlin::Transcript;
 use rand::RngCore;
 use rand_core::OsRng;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha512};
+use zeroize::Zeroize;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RingSignature {
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex::point"))]
     pub key_image: RistrettoPoint,
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex::scalars"))]
     pub c: Vec<Scalar>,
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex::scalars"))]
     pub r: Vec<Scalar>,
 }
 
@@ -30,23 +41,16 @@ impl RingSignature {
         let mut c = vec![Scalar::ZERO; n];
         let mut r = vec![Scalar::ZERO; n];
 
-        let alpha = generate_random_scalar();
+        let mut alpha = generate_random_scalar();
 
         let start_idx = (secret_index + 1) % n;
 
-        let mut hasher = Sha512::new();
-        hasher.update(b"RING_SIG_V1");
-        hasher.update(message);
-        hasher.update((alpha * RISTRETTO_BASEPOINT_POINT).compress().as_bytes());
-        hasher.update(
-            (alpha * hash_to_point(&public_keys[secret_index]))
-                .compress()
-                .as_bytes(),
+        c[start_idx] = hash_challenge(
+            message,
+            &(alpha * RISTRETTO_BASEPOINT_POINT),
+            &(alpha * hash_to_point(&public_keys[secret_index])),
         );
 
-        let hash = hasher.finalize();
-        c[start_idx] = Scalar::from_bytes_mod_order_wide(&hash.into());
-
         for i in 0..(n - 1) {
             let idx = (start_idx + i) % n;
             let next_idx = (idx + 1) % n;
@@ -57,17 +61,12 @@ impl RingSignature {
 
             let r_part = r[idx] * hash_to_point(&public_keys[idx]) + c[idx] * key_image;
 
-            let mut hasher = Sha512::new();
-            hasher.update(b"RING_SIG_V1");
-            hasher.update(message);
-            hasher.update(l.compress().as_bytes());
-            hasher.update(r_part.compress().as_bytes());
-
-            let hash = hasher.finalize();
-            c[next_idx] = Scalar::from_bytes_mod_order_wide(&hash.into());
+            c[next_idx] = hash_challenge(message, &l, &r_part);
         }
 
         r[secret_index] = alpha - c[secret_index] * secret_key;
+        // The nonce reveals the secret key together with the signature
+        alpha.zeroize();
 
         Self { key_image, c, r }
     }
@@ -83,23 +82,14 @@ impl RingSignature {
             return false;
         }
 
-        for i in 0..n {
+        for (i, public_key) in public_keys.iter().enumerate() {
             let next_i = (i + 1) % n;
 
-            let l = self.r[i] * RISTRETTO_BASEPOINT_POINT + self.c[i] * public_keys[i];
+            let l = self.r[i] * RISTRETTO_BASEPOINT_POINT + self.c[i] * public_key;
 
-            let r_part = self.r[i] * hash_to_point(&public_keys[i]) + self.c[i] * self.key_image;
+            let r_part = self.r[i] * hash_to_point(public_key) + self.c[i] * self.key_image;
 
-            let mut hasher = Sha512::new();
-            hasher.update(b"RING_SIG_V1");
-            hasher.update(message);
-            hasher.update(l.compress().as_bytes());
-            hasher.update(r_part.compress().as_bytes());
-
-            let hash = hasher.finalize();
-            let computed_c = Scalar::from_bytes_mod_order_wide(&hash.into());
-
-            if computed_c != self.c[next_i] {
+            if hash_challenge(message, &l, &r_part) != self.c[next_i] {
                 return false;
             }
         }
@@ -115,20 +105,122 @@ impl RingSignature {
     }
 }
 
-fn compute_key_image(secret_key: &Scalar, public_key: &RistrettoPoint) -> RistrettoPoint {
+impl From<&RingSignature> for RingSignatureData {
+    fn from(signature: &RingSignature) -> Self {
+        RingSignatureData::new(
+            signature.key_image.compress().to_bytes(),
+            signature.c.iter().map(|c| c.to_bytes()).collect(),
+            signature.r.iter().map(|r| r.to_bytes()).collect(),
+        )
+    }
+}
+
+impl From<RingSignature> for RingSignatureData {
+    fn from(signature: RingSignature) -> Self {
+        Self::from(&signature)
+    }
+}
+
+/// Rejects a key image that does not decompress, non-canonical scalars and
+/// mismatched challenge/response counts.
+impl TryFrom<&RingSignatureData> for RingSignature {
+    type Error = CryptoError;
+
+    fn try_from(data: &RingSignatureData) -> Result<Self> {
+        if data.c.is_empty() || data.c.len() != data.r.len() {
+            return Err(CryptoError::Deserialization(format!(
+                "ring signature has {} challenges and {} responses",
+                data.c.len(),
+                data.r.len()
+            )));
+        }
+
+        Ok(Self {
+            key_image: decompress_point(&data.key_image)?,
+            c: data.c.iter().map(canonical_scalar).collect::<Result<_>>()?,
+            r: data.r.iter().map(canonical_scalar).collect::<Result<_>>()?,
+        })
+    }
+}
+
+impl TryFrom<RingSignatureData> for RingSignature {
+    type Error = CryptoError;
+
+    fn try_from(data: RingSignatureData) -> Result<Self> {
+        Self::try_from(&data)
+    }
+}
+
+/// `I = x * Hp(P)`. Fixed per output, so it marks the output as spent.
+pub fn compute_key_image(secret_key: &Scalar, public_key: &RistrettoPoint) -> RistrettoPoint {
     let hash_point = hash_to_point(public_key);
     secret_key * hash_point
 }
 
-fn hash_to_point(point: &RistrettoPoint) -> RistrettoPoint {
+/// Shows that `I = x*Hp(P)` for the `x` with `P = x*G`, outside of a ring
+/// signature. Lets the spending wallet hand key images to a view-only wallet
+/// or an auditor, who cannot compute them.
+pub fn prove_key_image(
+    secret_key: &Scalar,
+    public_key: &RistrettoPoint,
+    context: &[u8],
+) -> (RistrettoPoint, DleqProof) {
+    let key_image = compute_key_image(secret_key, public_key);
+    let proof = DleqProof::prove(
+        &mut key_image_transcript(context),
+        &key_image_statement(public_key, &key_image),
+        secret_key,
+    );
+    (key_image, proof)
+}
+
+pub fn verify_key_image(
+    proof: &DleqProof,
+    public_key: &RistrettoPoint,
+    key_image: &RistrettoPoint,
+    context: &[u8],
+) -> Result<()> {
+    proof.verify(
+        &mut key_image_transcript(context),
+        &key_image_statement(public_key, key_image),
+    )
+}
+
+fn key_image_statement(public_key: &RistrettoPoint, key_image: &RistrettoPoint) -> DleqStatement {
+    DleqStatement {
+        base_g: RISTRETTO_BASEPOINT_POINT,
+        public_g: *public_key,
+        base_h: hash_to_point(public_key),
+        public_h: *key_image,
+    }
+}
+
+fn key_image_transcript(context: &[u8]) -> Transcript {
+    let mut transcript = Transcript::new(b"GELAP_KEY_IMAGE_PROOF_V1");
+    transcript.append_message(b"context", context);
+    transcript
+}
+
+pub(crate) fn hash_to_point(point: &RistrettoPoint) -> RistrettoPoint {
     let mut hasher = Sha512::new();
-    hasher.update(b"HASH_TO_POINT_V1");
+    hasher.update(HASH_TO_POINT_DOMAIN);
     hasher.update(point.compress().as_bytes());
     let hash = hasher.finalize();
 
     RistrettoPoint::from_uniform_bytes(&hash.into())
 }
 
+pub(crate) fn hash_challenge(message: &[u8], l: &RistrettoPoint, r: &RistrettoPoint) -> Scalar {
+    let mut hasher = Sha512::new();
+    hasher.update(RING_SIG_DOMAIN);
+    hasher.update(message);
+    hasher.update(l.compress().as_bytes());
+    hasher.update(r.compress().as_bytes());
+
+    let hash = hasher.finalize();
+    Scalar::from_bytes_mod_order_wide(&hash.into())
+}
+
 fn generate_random_scalar() -> Scalar {
     let mut bytes = [0u8; 64];
     OsRng.fill_bytes(&mut bytes);
@@ -170,6 +262,17 @@ mod tests {
         (secret_keys, public_keys)
     }
 
+    // Pinned so a change, which breaks every existing key image, cannot go
+    // unnoticed
+    #[test]
+    fn test_hash_to_point_vector() {
+        let point = hash_to_point(&RISTRETTO_BASEPOINT_POINT);
+        assert_eq!(
+            hex::encode(point.compress().to_bytes()),
+            "004bc6cf6f31f8f88357ea4d895cdd0b6ada154c88ac9a50aa7b54c7ab598a35"
+        );
+    }
+
     #[test]
     fn test_ring_signature_basic() {
         let ring_size = 8;
@@ -346,4 +449,55 @@ mod tests {
 
         assert_eq!(ki1, ki2);
     }
+
+    #[test]
+    fn test_key_image_proof() {
+        let sk = generate_random_scalar();
+        let pk = sk * RISTRETTO_BASEPOINT_POINT;
+
+        let (key_image, proof) = prove_key_image(&sk, &pk, b"sync");
+        assert_eq!(key_image, compute_key_image(&sk, &pk));
+        assert!(verify_key_image(&proof, &pk, &key_image, b"sync").is_ok());
+        assert!(verify_key_image(&proof, &pk, &key_image, b"other").is_err());
+
+        // A key image made with another secret cannot be passed off for pk
+        let wrong = compute_key_image(&generate_random_scalar(), &pk);
+        assert!(verify_key_image(&proof, &pk, &wrong, b"sync").is_err());
+    }
+
+    #[test]
+    fn test_ring_signature_data_conversion() {
+        let (secret_keys, public_keys) = create_ring(4);
+        let message = b"wire";
+        let signature = RingSignature::sign(message, &secret_keys[1], 1, &public_keys);
+
+        let data = RingSignatureData::from(&signature);
+        assert_eq!(data.key_image, signature.key_image.compress().to_bytes());
+        assert_eq!(data.ring_size(), 4);
+
+        let decoded = RingSignature::try_from(&data).unwrap();
+        assert!(decoded.verify(message, &public_keys));
+
+        let mut bad = data.clone();
+        bad.key_image = [0xff; 32];
+        assert!(matches!(
+            RingSignature::try_from(&bad),
+            Err(CryptoError::InvalidRisettoPoints)
+        ));
+
+        // c + l is the same scalar mod l but not its canonical encoding
+        let mut bad = data.clone();
+        bad.c[0] = [0xff; 32];
+        assert!(matches!(
+            RingSignature::try_from(&bad),
+            Err(CryptoError::InvalidScalar)
+        ));
+
+        let mut bad = data;
+        bad.r.pop();
+        assert!(matches!(
+            RingSignature::try_from(bad),
+            Err(CryptoError::Deserialization(_))
+        ));
+    }
 }
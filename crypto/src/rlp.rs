@@ -0,0 +1,95 @@
+// Recursive Length Prefix encoding, as used by Ethereum transactions.
+
+pub fn encode_bytes(bytes: &[u8]) -> Vec<u8> {
+    if bytes.len() == 1 && bytes[0] < 0x80 {
+        return bytes.to_vec();
+    }
+
+    let mut out = encode_length(bytes.len(), 0x80);
+    out.extend_from_slice(bytes);
+    out
+}
+
+/// Integers are encoded big-endian with no leading zeros; zero is the empty
+/// string.
+pub fn encode_uint(value: u128) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    encode_bytes(&bytes[start..])
+}
+
+/// Encodes a 256-bit big-endian integer, such as a signature scalar.
+pub fn encode_uint256(value: &[u8; 32]) -> Vec<u8> {
+    let start = value.iter().position(|b| *b != 0).unwrap_or(value.len());
+    encode_bytes(&value[start..])
+}
+
+/// Wraps already-encoded items into a list.
+pub fn encode_list(items: &[Vec<u8>]) -> Vec<u8> {
+    let payload: Vec<u8> = items.concat();
+
+    let mut out = encode_length(payload.len(), 0xc0);
+    out.extend_from_slice(&payload);
+    out
+}
+
+fn encode_length(len: usize, offset: u8) -> Vec<u8> {
+    if len < 56 {
+        return vec![offset + len as u8];
+    }
+
+    let len_bytes = (len as u64).to_be_bytes();
+    let start = len_bytes.iter().position(|b| *b != 0).unwrap_or(7);
+
+    let mut out = vec![offset + 55 + (8 - start) as u8];
+    out.extend_from_slice(&len_bytes[start..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_bytes() {
+        assert_eq!(encode_bytes(b""), vec![0x80]);
+        assert_eq!(encode_bytes(&[0x0f]), vec![0x0f]);
+        assert_eq!(encode_bytes(&[0x80]), vec![0x81, 0x80]);
+        assert_eq!(encode_bytes(b"dog"), vec![0x83, b'd', b'o', b'g']);
+
+        let long = b"Lorem ipsum dolor sit amet, consectetur adipisicing elit";
+        let encoded = encode_bytes(long);
+        assert_eq!(&encoded[..2], &[0xb8, 0x38]);
+        assert_eq!(&encoded[2..], long);
+    }
+
+    #[test]
+    fn test_encode_uint() {
+        assert_eq!(encode_uint(0), vec![0x80]);
+        assert_eq!(encode_uint(15), vec![0x0f]);
+        assert_eq!(encode_uint(1024), vec![0x82, 0x04, 0x00]);
+
+        let mut word = [0u8; 32];
+        word[31] = 0x7f;
+        assert_eq!(encode_uint256(&word), vec![0x7f]);
+        assert_eq!(encode_uint256(&[0u8; 32]), vec![0x80]);
+    }
+
+    #[test]
+    fn test_encode_list() {
+        assert_eq!(encode_list(&[]), vec![0xc0]);
+        assert_eq!(
+            encode_list(&[encode_bytes(b"cat"), encode_bytes(b"dog")]),
+            vec![0xc8, 0x83, b'c', b'a', b't', 0x83, b'd', b'o', b'g']
+        );
+
+        // [ [], [[]], [ [], [[]] ] ]
+        let nested = || encode_list(&[encode_list(&[])]);
+        let set = encode_list(&[
+            encode_list(&[]),
+            nested(),
+            encode_list(&[encode_list(&[]), nested()]),
+        ]);
+        assert_eq!(set, vec![0xc7, 0xc0, 0xc1, 0xc0, 0xc3, 0xc0, 0xc1, 0xc0]);
+    }
+}
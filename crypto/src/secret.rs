@@ -0,0 +1,76 @@
+// Holder for secret values that are wiped from memory on drop and never show
+// up in Debug output or logs.
+
+use std::fmt;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// A secret that is zeroized when dropped. It is deliberately not `Clone`:
+/// copies made through [`Secret::expose`] are visible at the call site.
+pub struct Secret<T: Zeroize>(T);
+
+impl<T: Zeroize> Secret<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Zeroize> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: Zeroize> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: Zeroize> ZeroizeOnDrop for Secret<T> {}
+
+impl<T: Zeroize> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret(<redacted>)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use curve25519_dalek::scalar::Scalar;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_debug_is_redacted() {
+        let secret = Secret::new(Scalar::from(0xdead_beefu64));
+        let debug = format!("{:?}", secret);
+
+        assert_eq!(debug, "Secret(<redacted>)");
+        assert_eq!(*secret.expose(), Scalar::from(0xdead_beefu64));
+    }
+
+    /// Records whether it was zeroized, since the value itself is gone
+    /// once the secret is dropped.
+    struct Tracked(Rc<Cell<bool>>);
+
+    impl Zeroize for Tracked {
+        fn zeroize(&mut self) {
+            self.0.set(true);
+        }
+    }
+
+    #[test]
+    fn test_drop_zeroizes_value() {
+        let wiped = Rc::new(Cell::new(false));
+        let secret = Secret::new(Tracked(wiped.clone()));
+        assert!(!wiped.get());
+
+        drop(secret);
+        assert!(wiped.get());
+    }
+}
@@ -0,0 +1,188 @@
+// `0x`-prefixed hex for Ristretto points and scalars in human-readable
+// formats. Binary formats use curve25519-dalek's own 32-byte encoding, so
+// bincode output is the same with or without the `hex` feature.
+//
+// Decoding rejects points that do not decompress and non-canonical scalars.
+
+use bulletproofs::RangeProof;
+use cryptography_types::serde_hex::{decode, decode_array, encode};
+use curve25519_dalek::{
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A Ristretto point serialized as its compressed encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HexPoint(pub RistrettoPoint);
+
+/// A scalar serialized as its canonical little-endian encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HexScalar(pub Scalar);
+
+impl Serialize for HexPoint {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&encode(self.0.compress().as_bytes()))
+        } else {
+            self.0.serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for HexPoint {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if !deserializer.is_human_readable() {
+            return RistrettoPoint::deserialize(deserializer).map(HexPoint);
+        }
+
+        let s = String::deserialize(deserializer)?;
+        let bytes = decode_array::<32>(&s).map_err(D::Error::custom)?;
+        CompressedRistretto(bytes)
+            .decompress()
+            .map(HexPoint)
+            .ok_or_else(|| D::Error::custom("invalid Ristretto point"))
+    }
+}
+
+impl Serialize for HexScalar {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&encode(self.0.as_bytes()))
+        } else {
+            self.0.serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for HexScalar {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if !deserializer.is_human_readable() {
+            return Scalar::deserialize(deserializer).map(HexScalar);
+        }
+
+        let s = String::deserialize(deserializer)?;
+        let bytes = decode_array::<32>(&s).map_err(D::Error::custom)?;
+        Option::from(Scalar::from_canonical_bytes(bytes))
+            .map(HexScalar)
+            .ok_or_else(|| D::Error::custom("non-canonical scalar"))
+    }
+}
+
+/// `#[serde(with = "crate::serde_hex::point")]`
+pub mod point {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        point: &RistrettoPoint,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        HexPoint(*point).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<RistrettoPoint, D::Error> {
+        HexPoint::deserialize(deserializer).map(|p| p.0)
+    }
+}
+
+/// `#[serde(with = "crate::serde_hex::scalar")]`
+pub mod scalar {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(scalar: &Scalar, serializer: S) -> Result<S::Ok, S::Error> {
+        HexScalar(*scalar).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Scalar, D::Error> {
+        HexScalar::deserialize(deserializer).map(|s| s.0)
+    }
+}
+
+/// `#[serde(with = "crate::serde_hex::range_proof")]`: one hex string of
+/// the Bulletproofs byte encoding. Used with or without the `hex` feature,
+/// since `RangeProof`'s own impl does not round-trip through JSON.
+pub mod range_proof {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(proof: &RangeProof, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&encode(&proof.to_bytes()))
+        } else {
+            proof.serialize(serializer)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<RangeProof, D::Error> {
+        if !deserializer.is_human_readable() {
+            return RangeProof::deserialize(deserializer);
+        }
+
+        let s = String::deserialize(deserializer)?;
+        let bytes = decode(&s).map_err(D::Error::custom)?;
+        RangeProof::from_bytes(&bytes).map_err(D::Error::custom)
+    }
+}
+
+/// `#[serde(with = "crate::serde_hex::scalars")]` for `Vec<Scalar>`.
+pub mod scalars {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(scalars: &[Scalar], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(scalars.iter().map(|s| HexScalar(*s)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Scalar>, D::Error> {
+        let scalars = Vec::<HexScalar>::deserialize(deserializer)?;
+        Ok(scalars.into_iter().map(|s| s.0).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+
+    #[test]
+    fn test_point_json() {
+        let point = Scalar::from(7u64) * RISTRETTO_BASEPOINT_POINT;
+        let json = serde_json::to_string(&HexPoint(point)).unwrap();
+        assert_eq!(json, format!("\"{}\"", encode(point.compress().as_bytes())));
+        assert_eq!(serde_json::from_str::<HexPoint>(&json).unwrap().0, point);
+
+        // 0xff.. is not a valid encoding.
+        let bad = format!("\"0x{}\"", "ff".repeat(32));
+        assert!(serde_json::from_str::<HexPoint>(&bad).is_err());
+        assert!(serde_json::from_str::<HexPoint>("\"0x00\"").is_err());
+    }
+
+    #[test]
+    fn test_scalar_json() {
+        let scalar = Scalar::from(42u64);
+        let json = serde_json::to_string(&HexScalar(scalar)).unwrap();
+        assert_eq!(json, format!("\"0x2a{}\"", "00".repeat(31)));
+        assert_eq!(serde_json::from_str::<HexScalar>(&json).unwrap().0, scalar);
+
+        let non_canonical = format!("\"0x{}\"", "ff".repeat(32));
+        assert!(serde_json::from_str::<HexScalar>(&non_canonical).is_err());
+    }
+
+    #[test]
+    fn test_bincode_unchanged() {
+        let point = Scalar::from(7u64) * RISTRETTO_BASEPOINT_POINT;
+        let scalar = Scalar::from(42u64);
+
+        let bytes = bincode::serialize(&HexPoint(point)).unwrap();
+        assert_eq!(bytes, bincode::serialize(&point).unwrap());
+        assert_eq!(bytes.len(), 32);
+        assert_eq!(bincode::deserialize::<HexPoint>(&bytes).unwrap().0, point);
+
+        let bytes = bincode::serialize(&HexScalar(scalar)).unwrap();
+        assert_eq!(bytes, bincode::serialize(&scalar).unwrap());
+        assert_eq!(bincode::deserialize::<HexScalar>(&bytes).unwrap().0, scalar);
+    }
+}
@@ -0,0 +1,404 @@
+// Sigma protocols on Ristretto, made non-interactive with Merlin transcripts.
+//
+// - `SchnorrProof`: knowledge of x with P = x*B.
+// - `DleqProof`: Chaum-Pedersen proof that log_G(P) = log_H(Q).
+//
+// The caller creates the transcript with its own protocol label and appends
+// whatever context the proof must bind (commitments, amounts, a verifier
+// nonce) before proving or verifying. The statement itself is appended here.
+//
+// Ring signatures keep their own SHA-512 challenge, which the zkVM guest
+// recomputes byte for byte.
+
+use crate::errors::{CryptoError, Result};
+use crate::pedersen::generate_blinding;
+use crate::utils::{canonical_scalar, decompress_point};
+use curve25519_dalek::{
+    ristretto::RistrettoPoint,
+    scalar::Scalar,
+    traits::{IsIdentity, VartimeMultiscalarMul},
+};
+use merlin::Transcript;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+/// Ristretto helpers on top of [`merlin::Transcript`].
+pub trait TranscriptProtocol {
+    fn append_point(&mut self, label: &'static [u8], point: &RistrettoPoint);
+    fn append_scalar(&mut self, label: &'static [u8], scalar: &Scalar);
+    /// A uniform scalar from 64 bytes of transcript output.
+    fn challenge_scalar(&mut self, label: &'static [u8]) -> Scalar;
+}
+
+impl TranscriptProtocol for Transcript {
+    fn append_point(&mut self, label: &'static [u8], point: &RistrettoPoint) {
+        self.append_message(label, point.compress().as_bytes());
+    }
+
+    fn append_scalar(&mut self, label: &'static [u8], scalar: &Scalar) {
+        self.append_message(label, scalar.as_bytes());
+    }
+
+    fn challenge_scalar(&mut self, label: &'static [u8]) -> Scalar {
+        let mut bytes = [0u8; 64];
+        self.challenge_bytes(label, &mut bytes);
+        Scalar::from_bytes_mod_order_wide(&bytes)
+    }
+}
+
+/// `public = x * base`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SchnorrStatement {
+    pub base: RistrettoPoint,
+    pub public: RistrettoPoint,
+}
+
+/// `public_g = x * base_g` and `public_h = x * base_h` for the same x.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DleqStatement {
+    pub base_g: RistrettoPoint,
+    pub public_g: RistrettoPoint,
+    pub base_h: RistrettoPoint,
+    pub public_h: RistrettoPoint,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SchnorrProof {
+    /// `k * base`
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex::point"))]
+    pub nonce: RistrettoPoint,
+    /// `k + e*x`
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex::scalar"))]
+    pub response: Scalar,
+}
+
+impl SchnorrProof {
+    /// nonce (32) || response (32)
+    pub const SIZE: usize = 64;
+
+    /// `secret` must open `statement`; the proof will not verify otherwise.
+    pub fn prove(
+        transcript: &mut Transcript,
+        statement: &SchnorrStatement,
+        secret: &Scalar,
+    ) -> Self {
+        let mut k = generate_blinding();
+        let nonce = k * statement.base;
+
+        let challenge = schnorr_challenge(transcript, statement, &nonce);
+        let response = k + challenge * secret;
+        k.zeroize();
+
+        Self { nonce, response }
+    }
+
+    pub fn verify(&self, transcript: &mut Transcript, statement: &SchnorrStatement) -> Result<()> {
+        let challenge = schnorr_challenge(transcript, statement, &self.nonce);
+
+        if self.response * statement.base == self.nonce + challenge * statement.public {
+            Ok(())
+        } else {
+            Err(CryptoError::ProofVerificationFailed)
+        }
+    }
+
+    /// Verifies many proofs with one multiscalar multiplication, each with
+    /// its own transcript. Fails if any proof is invalid, without saying which.
+    pub fn verify_batch(
+        proofs: &[Self],
+        transcripts: &mut [Transcript],
+        statements: &[SchnorrStatement],
+    ) -> Result<()> {
+        check_batch_lengths(proofs.len(), transcripts.len(), statements.len())?;
+
+        let mut scalars = Vec::with_capacity(3 * proofs.len());
+        let mut points = Vec::with_capacity(3 * proofs.len());
+
+        for ((proof, transcript), statement) in proofs.iter().zip(transcripts).zip(statements) {
+            let challenge = schnorr_challenge(transcript, statement, &proof.nonce);
+            let weight = generate_blinding();
+
+            // w * (s*B - R - e*P)
+            scalars.extend([weight * proof.response, -weight, -weight * challenge]);
+            points.extend([statement.base, proof.nonce, statement.public]);
+        }
+
+        check_batch(scalars, points)
+    }
+
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0u8; Self::SIZE];
+        bytes[..32].copy_from_slice(self.nonce.compress().as_bytes());
+        bytes[32..].copy_from_slice(self.response.as_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != Self::SIZE {
+            return Err(CryptoError::Deserialization(format!(
+                "Schnorr proof must be {} bytes, got {}",
+                Self::SIZE,
+                bytes.len()
+            )));
+        }
+
+        Ok(Self {
+            nonce: decompress_point(bytes[..32].try_into().expect("32 bytes"))?,
+            response: canonical_scalar(bytes[32..].try_into().expect("32 bytes"))?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DleqProof {
+    /// `k * base_g`
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex::point"))]
+    pub nonce_g: RistrettoPoint,
+    /// `k * base_h`
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex::point"))]
+    pub nonce_h: RistrettoPoint,
+    /// `k + e*x`
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex::scalar"))]
+    pub response: Scalar,
+}
+
+impl DleqProof {
+    /// nonce_g (32) || nonce_h (32) || response (32)
+    pub const SIZE: usize = 96;
+
+    /// `secret` must open `statement`; the proof will not verify otherwise.
+    pub fn prove(transcript: &mut Transcript, statement: &DleqStatement, secret: &Scalar) -> Self {
+        let mut k = generate_blinding();
+        let nonce_g = k * statement.base_g;
+        let nonce_h = k * statement.base_h;
+
+        let challenge = dleq_challenge(transcript, statement, &nonce_g, &nonce_h);
+        let response = k + challenge * secret;
+        k.zeroize();
+
+        Self {
+            nonce_g,
+            nonce_h,
+            response,
+        }
+    }
+
+    pub fn verify(&self, transcript: &mut Transcript, statement: &DleqStatement) -> Result<()> {
+        let challenge = dleq_challenge(transcript, statement, &self.nonce_g, &self.nonce_h);
+
+        if self.response * statement.base_g == self.nonce_g + challenge * statement.public_g
+            && self.response * statement.base_h == self.nonce_h + challenge * statement.public_h
+        {
+            Ok(())
+        } else {
+            Err(CryptoError::ProofVerificationFailed)
+        }
+    }
+
+    /// Verifies many proofs with one multiscalar multiplication, each with
+    /// its own transcript. Fails if any proof is invalid, without saying which.
+    pub fn verify_batch(
+        proofs: &[Self],
+        transcripts: &mut [Transcript],
+        statements: &[DleqStatement],
+    ) -> Result<()> {
+        check_batch_lengths(proofs.len(), transcripts.len(), statements.len())?;
+
+        let mut scalars = Vec::with_capacity(6 * proofs.len());
+        let mut points = Vec::with_capacity(6 * proofs.len());
+
+        for ((proof, transcript), statement) in proofs.iter().zip(transcripts).zip(statements) {
+            let challenge = dleq_challenge(transcript, statement, &proof.nonce_g, &proof.nonce_h);
+            let weight_g = generate_blinding();
+            let weight_h = generate_blinding();
+
+            scalars.extend([
+                weight_g * proof.response,
+                -weight_g,
+                -weight_g * challenge,
+                weight_h * proof.response,
+                -weight_h,
+                -weight_h * challenge,
+            ]);
+            points.extend([
+                statement.base_g,
+                proof.nonce_g,
+                statement.public_g,
+                statement.base_h,
+                proof.nonce_h,
+                statement.public_h,
+            ]);
+        }
+
+        check_batch(scalars, points)
+    }
+
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0u8; Self::SIZE];
+        bytes[..32].copy_from_slice(self.nonce_g.compress().as_bytes());
+        bytes[32..64].copy_from_slice(self.nonce_h.compress().as_bytes());
+        bytes[64..].copy_from_slice(self.response.as_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != Self::SIZE {
+            return Err(CryptoError::Deserialization(format!(
+                "DLEQ proof must be {} bytes, got {}",
+                Self::SIZE,
+                bytes.len()
+            )));
+        }
+
+        Ok(Self {
+            nonce_g: decompress_point(bytes[..32].try_into().expect("32 bytes"))?,
+            nonce_h: decompress_point(bytes[32..64].try_into().expect("32 bytes"))?,
+            response: canonical_scalar(bytes[64..].try_into().expect("32 bytes"))?,
+        })
+    }
+}
+
+fn schnorr_challenge(
+    transcript: &mut Transcript,
+    statement: &SchnorrStatement,
+    nonce: &RistrettoPoint,
+) -> Scalar {
+    transcript.append_message(b"proof", b"schnorr");
+    transcript.append_point(b"B", &statement.base);
+    transcript.append_point(b"P", &statement.public);
+    transcript.append_point(b"R", nonce);
+    transcript.challenge_scalar(b"e")
+}
+
+fn dleq_challenge(
+    transcript: &mut Transcript,
+    statement: &DleqStatement,
+    nonce_g: &RistrettoPoint,
+    nonce_h: &RistrettoPoint,
+) -> Scalar {
+    transcript.append_message(b"proof", b"dleq");
+    transcript.append_point(b"G", &statement.base_g);
+    transcript.append_point(b"P", &statement.public_g);
+    transcript.append_point(b"H", &statement.base_h);
+    transcript.append_point(b"Q", &statement.public_h);
+    transcript.append_point(b"R_G", nonce_g);
+    transcript.append_point(b"R_H", nonce_h);
+    transcript.challenge_scalar(b"e")
+}
+
+fn check_batch_lengths(proofs: usize, transcripts: usize, statements: usize) -> Result<()> {
+    if proofs != transcripts || proofs != statements {
+        return Err(CryptoError::InvalidInput(format!(
+            "Batch has {} proofs, {} transcripts and {} statements",
+            proofs, transcripts, statements
+        )));
+    }
+    Ok(())
+}
+
+fn check_batch(scalars: Vec<Scalar>, points: Vec<RistrettoPoint>) -> Result<()> {
+    if RistrettoPoint::vartime_multiscalar_mul(scalars, points).is_identity() {
+        Ok(())
+    } else {
+        Err(CryptoError::ProofVerificationFailed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pedersen::get_h_generator;
+    use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+
+    fn transcript(context: &[u8]) -> Transcript {
+        let mut transcript = Transcript::new(b"GELAP_SIGMA_TEST");
+        transcript.append_message(b"context", context);
+        transcript
+    }
+
+    fn schnorr() -> (SchnorrStatement, Scalar) {
+        let secret = generate_blinding();
+        let base = get_h_generator();
+        let statement = SchnorrStatement {
+            base,
+            public: secret * base,
+        };
+        (statement, secret)
+    }
+
+    fn dleq() -> (DleqStatement, Scalar) {
+        let secret = generate_blinding();
+        let base_h = get_h_generator();
+        let statement = DleqStatement {
+            base_g: RISTRETTO_BASEPOINT_POINT,
+            public_g: secret * RISTRETTO_BASEPOINT_POINT,
+            base_h,
+            public_h: secret * base_h,
+        };
+        (statement, secret)
+    }
+
+    #[test]
+    fn test_schnorr_proof() {
+        let (statement, secret) = schnorr();
+        let proof = SchnorrProof::prove(&mut transcript(b"a"), &statement, &secret);
+
+        assert!(proof.verify(&mut transcript(b"a"), &statement).is_ok());
+        assert!(proof.verify(&mut transcript(b"b"), &statement).is_err());
+
+        let (other, _) = schnorr();
+        assert!(proof.verify(&mut transcript(b"a"), &other).is_err());
+
+        let decoded = SchnorrProof::from_bytes(&proof.to_bytes()).unwrap();
+        assert_eq!(decoded, proof);
+        assert!(SchnorrProof::from_bytes(&proof.to_bytes()[1..]).is_err());
+    }
+
+    #[test]
+    fn test_dleq_proof() {
+        let (statement, secret) = dleq();
+        let proof = DleqProof::prove(&mut transcript(b""), &statement, &secret);
+        assert!(proof.verify(&mut transcript(b""), &statement).is_ok());
+
+        // Same secret on G, a different one on H
+        let mut unequal = statement;
+        unequal.public_h = generate_blinding() * statement.base_h;
+        let forged = DleqProof::prove(&mut transcript(b""), &unequal, &secret);
+        assert!(forged.verify(&mut transcript(b""), &unequal).is_err());
+
+        let json = serde_json::to_string(&proof).unwrap();
+        let decoded: DleqProof = serde_json::from_str(&json).unwrap();
+        assert_eq!(DleqProof::from_bytes(&decoded.to_bytes()).unwrap(), proof);
+    }
+
+    #[test]
+    fn test_batch_verification() {
+        let schnorr: Vec<_> = (0..4).map(|_| schnorr()).collect();
+        let statements: Vec<_> = schnorr.iter().map(|(s, _)| *s).collect();
+        let mut proofs: Vec<_> = schnorr
+            .iter()
+            .map(|(s, x)| SchnorrProof::prove(&mut transcript(b"batch"), s, x))
+            .collect();
+        let transcripts = || vec![transcript(b"batch"); 4];
+
+        assert!(SchnorrProof::verify_batch(&proofs, &mut transcripts(), &statements).is_ok());
+        proofs[2].response += Scalar::ONE;
+        assert!(SchnorrProof::verify_batch(&proofs, &mut transcripts(), &statements).is_err());
+        assert!(SchnorrProof::verify_batch(&proofs[..3], &mut transcripts(), &statements).is_err());
+
+        let dleq: Vec<_> = (0..3).map(|_| dleq()).collect();
+        let statements: Vec<_> = dleq.iter().map(|(s, _)| *s).collect();
+        let mut proofs: Vec<_> = dleq
+            .iter()
+            .map(|(s, x)| DleqProof::prove(&mut transcript(b""), s, x))
+            .collect();
+
+        assert!(
+            DleqProof::verify_batch(&proofs, &mut vec![transcript(b""); 3], &statements).is_ok()
+        );
+        proofs.swap(0, 1);
+        assert!(
+            DleqProof::verify_batch(&proofs, &mut vec![transcript(b""); 3], &statements).is_err()
+        );
+    }
+}
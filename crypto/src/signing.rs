@@ -0,0 +1,497 @@
+use crate::errors::{CryptoError, Result};
+use crate::ethereum::{pubkey_to_address, EthAddress, EthKeyPair};
+use crate::utils::hash_keccak256;
+use secp256k1::{
+    ecdsa::{RecoverableSignature, RecoveryId},
+    Message, Secp256k1, SecretKey,
+};
+use serde::{Deserialize, Serialize};
+
+/// Recoverable secp256k1 signature in Ethereum's `r || s || v` layout,
+/// with `v` in {27, 28}.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EthSignature {
+    pub r: [u8; 32],
+    pub s: [u8; 32],
+    pub v: u8,
+}
+
+impl EthSignature {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != 65 {
+            return Err(CryptoError::InvalidSignature);
+        }
+
+        let v = match bytes[64] {
+            0 | 1 => bytes[64] + 27,
+            27 | 28 => bytes[64],
+            _ => return Err(CryptoError::InvalidSignature),
+        };
+
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+        r.copy_from_slice(&bytes[..32]);
+        s.copy_from_slice(&bytes[32..64]);
+
+        Ok(Self { r, s, v })
+    }
+
+    pub fn to_bytes(&self) -> [u8; 65] {
+        let mut bytes = [0u8; 65];
+        bytes[..32].copy_from_slice(&self.r);
+        bytes[32..64].copy_from_slice(&self.s);
+        bytes[64] = self.v;
+        bytes
+    }
+
+    /// `v - 27`. Fails if `v` was set to anything but 27 or 28.
+    pub fn recovery_id(&self) -> Result<u8> {
+        self.v
+            .checked_sub(27)
+            .filter(|id| *id <= 1)
+            .ok_or(CryptoError::InvalidSignature)
+    }
+
+    pub fn to_recoverable(&self) -> Result<RecoverableSignature> {
+        let recid = RecoveryId::from_i32(self.recovery_id()? as i32)
+            .map_err(|_| CryptoError::InvalidSignature)?;
+
+        let mut compact = [0u8; 64];
+        compact[..32].copy_from_slice(&self.r);
+        compact[32..].copy_from_slice(&self.s);
+
+        RecoverableSignature::from_compact(&compact, recid)
+            .map_err(|_| CryptoError::InvalidSignature)
+    }
+
+    /// Returns the equivalent low-s signature, flipping the recovery id when
+    /// `s` had to be negated.
+    pub fn normalized(&self) -> Result<Self> {
+        let mut standard = self.to_recoverable()?.to_standard();
+        let original = standard.serialize_compact();
+        standard.normalize_s();
+        let compact = standard.serialize_compact();
+
+        let mut normalized = *self;
+        normalized.s.copy_from_slice(&compact[32..]);
+        if compact != original {
+            normalized.v = if self.v == 27 { 28 } else { 27 };
+        }
+
+        Ok(normalized)
+    }
+}
+
+impl From<RecoverableSignature> for EthSignature {
+    fn from(signature: RecoverableSignature) -> Self {
+        let (recid, compact) = signature.serialize_compact();
+
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+        r.copy_from_slice(&compact[..32]);
+        s.copy_from_slice(&compact[32..]);
+
+        Self {
+            r,
+            s,
+            v: recid.to_i32() as u8 + 27,
+        }
+    }
+}
+
+/// EIP-712 domain with the `name`, `version`, `chainId` and
+/// `verifyingContract` fields.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Eip712Domain {
+    pub name: String,
+    pub version: String,
+    pub chain_id: u64,
+    pub verifying_contract: EthAddress,
+}
+
+impl Eip712Domain {
+    pub const TYPE: &'static str =
+        "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+
+    pub fn new(name: &str, version: &str, chain_id: u64, verifying_contract: EthAddress) -> Self {
+        Self {
+            name: name.to_string(),
+            version: version.to_string(),
+            chain_id,
+            verifying_contract,
+        }
+    }
+
+    pub fn separator(&self) -> [u8; 32] {
+        let mut data = Vec::with_capacity(5 * 32);
+        data.extend_from_slice(&hash_keccak256(Self::TYPE.as_bytes()));
+        data.extend_from_slice(&encode_string(&self.name));
+        data.extend_from_slice(&encode_string(&self.version));
+        data.extend_from_slice(&encode_uint(self.chain_id as u128));
+        data.extend_from_slice(&encode_address(&self.verifying_contract));
+        hash_keccak256(&data)
+    }
+}
+
+/// A struct that can be signed as EIP-712 typed data.
+///
+/// `encode_type` is the full `encodeType` string, including referenced struct
+/// types in alphabetical order. `encode_data` is the concatenation of the
+/// 32-byte encoded members, in declaration order.
+pub trait Eip712Struct {
+    fn encode_type() -> String;
+
+    fn encode_data(&self) -> Vec<u8>;
+
+    fn type_hash() -> [u8; 32] {
+        hash_keccak256(Self::encode_type().as_bytes())
+    }
+
+    fn struct_hash(&self) -> [u8; 32] {
+        let mut data = Self::type_hash().to_vec();
+        data.extend_from_slice(&self.encode_data());
+        hash_keccak256(&data)
+    }
+}
+
+pub fn encode_string(value: &str) -> [u8; 32] {
+    hash_keccak256(value.as_bytes())
+}
+
+pub fn encode_bytes(value: &[u8]) -> [u8; 32] {
+    hash_keccak256(value)
+}
+
+pub fn encode_address(address: &EthAddress) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[12..].copy_from_slice(address);
+    word
+}
+
+pub fn encode_uint(value: u128) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[16..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+/// `keccak256("\x19\x01" || domainSeparator || hashStruct(message))`
+pub fn hash_typed_data<T: Eip712Struct>(domain: &Eip712Domain, message: &T) -> [u8; 32] {
+    let mut data = Vec::with_capacity(66);
+    data.extend_from_slice(&[0x19, 0x01]);
+    data.extend_from_slice(&domain.separator());
+    data.extend_from_slice(&message.struct_hash());
+    hash_keccak256(&data)
+}
+
+/// EIP-191 `personal_sign` digest:
+/// `keccak256("\x19Ethereum Signed Message:\n" || len(message) || message)`.
+pub fn hash_personal_message(message: &[u8]) -> [u8; 32] {
+    let mut data = format!("\x19Ethereum Signed Message:\n{}", message.len()).into_bytes();
+    data.extend_from_slice(message);
+    hash_keccak256(&data)
+}
+
+pub fn sign_hash(secret: &SecretKey, hash: &[u8; 32]) -> EthSignature {
+    let secp = Secp256k1::new();
+    let message = Message::from_digest(*hash);
+
+    secp.sign_ecdsa_recoverable(&message, secret).into()
+}
+
+pub fn recover_address(hash: &[u8; 32], signature: &EthSignature) -> Result<EthAddress> {
+    let secp = Secp256k1::new();
+    let message = Message::from_digest(*hash);
+
+    let pubkey = secp
+        .recover_ecdsa(&message, &signature.to_recoverable()?)
+        .map_err(|_| CryptoError::InvalidSignature)?;
+
+    Ok(pubkey_to_address(&pubkey))
+}
+
+pub fn recover_personal(message: &[u8], signature: &EthSignature) -> Result<EthAddress> {
+    recover_address(&hash_personal_message(message), signature)
+}
+
+pub fn verify_personal(address: &EthAddress, message: &[u8], signature: &EthSignature) -> bool {
+    matches!(recover_personal(message, signature), Ok(recovered) if recovered == *address)
+}
+
+pub fn recover_typed_data<T: Eip712Struct>(
+    domain: &Eip712Domain,
+    message: &T,
+    signature: &EthSignature,
+) -> Result<EthAddress> {
+    recover_address(&hash_typed_data(domain, message), signature)
+}
+
+pub fn verify_typed_data<T: Eip712Struct>(
+    address: &EthAddress,
+    domain: &Eip712Domain,
+    message: &T,
+    signature: &EthSignature,
+) -> bool {
+    matches!(
+        recover_typed_data(domain, message, signature),
+        Ok(recovered) if recovered == *address
+    )
+}
+
+impl EthKeyPair {
+    pub fn sign_hash(&self, hash: &[u8; 32]) -> EthSignature {
+        sign_hash(&self.secret, hash)
+    }
+
+    /// EIP-191 `personal_sign`.
+    pub fn personal_sign(&self, message: &[u8]) -> EthSignature {
+        self.sign_hash(&hash_personal_message(message))
+    }
+
+    /// EIP-712 `eth_signTypedData`.
+    pub fn sign_typed_data<T: Eip712Struct>(
+        &self,
+        domain: &Eip712Domain,
+        message: &T,
+    ) -> EthSignature {
+        self.sign_hash(&hash_typed_data(domain, message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ethereum::parse_address;
+
+    #[test]
+    fn test_personal_message_hash() {
+        let hash = hash_personal_message(b"Some data");
+        assert_eq!(
+            hex::encode(hash),
+            "1da44b586eb0729ff70a73c326926f6ed5a25f5b056e7f47fbc6e58d86871655"
+        );
+    }
+
+    struct Person {
+        name: String,
+        wallet: EthAddress,
+    }
+
+    impl Eip712Struct for Person {
+        fn encode_type() -> String {
+            "Person(string name,address wallet)".to_string()
+        }
+
+        fn encode_data(&self) -> Vec<u8> {
+            [encode_string(&self.name), encode_address(&self.wallet)].concat()
+        }
+    }
+
+    struct Mail {
+        from: Person,
+        to: Person,
+        contents: String,
+    }
+
+    impl Eip712Struct for Mail {
+        fn encode_type() -> String {
+            format!(
+                "Mail(Person from,Person to,string contents){}",
+                Person::encode_type()
+            )
+        }
+
+        fn encode_data(&self) -> Vec<u8> {
+            [
+                self.from.struct_hash(),
+                self.to.struct_hash(),
+                encode_string(&self.contents),
+            ]
+            .concat()
+        }
+    }
+
+    fn eip712_example() -> (Eip712Domain, Mail) {
+        let domain = Eip712Domain::new(
+            "Ether Mail",
+            "1",
+            1,
+            parse_address("0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC").unwrap(),
+        );
+
+        let mail = Mail {
+            from: Person {
+                name: "Cow".to_string(),
+                wallet: parse_address("0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826").unwrap(),
+            },
+            to: Person {
+                name: "Bob".to_string(),
+                wallet: parse_address("0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB").unwrap(),
+            },
+            contents: "Hello, Bob!".to_string(),
+        };
+
+        (domain, mail)
+    }
+
+    #[test]
+    fn test_personal_sign_vector() {
+        let secret = SecretKey::from_slice(
+            &hex::decode("4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318")
+                .unwrap(),
+        )
+        .unwrap();
+        let keypair = EthKeyPair::from_secret(secret).unwrap();
+
+        let signature = keypair.personal_sign(b"Some data");
+        assert_eq!(
+            hex::encode(signature.to_bytes()),
+            "b91467e570a6466aa9e9876cbcd013baba02900b8979d43fe208a4a4f339f5fd6007e74cd82e037b800186422fc2da167c747ef045e5d18a5f5d4300f8e1a0291c"
+        );
+
+        assert_eq!(
+            recover_personal(b"Some data", &signature).unwrap(),
+            keypair.address
+        );
+        assert!(verify_personal(&keypair.address, b"Some data", &signature));
+        assert!(!verify_personal(
+            &keypair.address,
+            b"Other data",
+            &signature
+        ));
+    }
+
+    #[test]
+    fn test_eip712_vector() {
+        let (domain, mail) = eip712_example();
+
+        assert_eq!(
+            hex::encode(domain.separator()),
+            "f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f"
+        );
+        assert_eq!(
+            hex::encode(mail.struct_hash()),
+            "c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e"
+        );
+        assert_eq!(
+            hex::encode(hash_typed_data(&domain, &mail)),
+            "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"
+        );
+
+        let secret = SecretKey::from_slice(&hash_keccak256(b"cow")).unwrap();
+        let keypair = EthKeyPair::from_secret(secret).unwrap();
+        assert_eq!(keypair.address, mail.from.wallet);
+
+        let signature = keypair.sign_typed_data(&domain, &mail);
+        assert_eq!(signature.v, 28);
+        assert_eq!(
+            hex::encode(signature.r),
+            "4355c47d63924e8a72e509b65029052eb6c299d53a04e167c5775fd466751c9d"
+        );
+        assert_eq!(
+            hex::encode(signature.s),
+            "07299936d304c153f6443dfa05f40ff007d72911b6f72307f996231605b91562"
+        );
+
+        assert!(verify_typed_data(
+            &keypair.address,
+            &domain,
+            &mail,
+            &signature
+        ));
+    }
+
+    #[test]
+    fn test_typed_data_domain_binding() {
+        let (domain, mail) = eip712_example();
+        let keypair = EthKeyPair::random().unwrap();
+
+        let signature = keypair.sign_typed_data(&domain, &mail);
+        assert_eq!(
+            recover_typed_data(&domain, &mail, &signature).unwrap(),
+            pubkey_to_address(&keypair.public)
+        );
+
+        let other_chain = Eip712Domain {
+            chain_id: 5,
+            ..domain.clone()
+        };
+        assert!(!verify_typed_data(
+            &keypair.address,
+            &other_chain,
+            &mail,
+            &signature
+        ));
+    }
+
+    #[test]
+    fn test_sign_and_recover() {
+        let keypair = EthKeyPair::random().unwrap();
+        let hash = hash_personal_message(b"hello");
+
+        let signature = sign_hash(&keypair.secret, &hash);
+        assert!(signature.v == 27 || signature.v == 28);
+
+        let recovered = recover_address(&hash, &signature).unwrap();
+        assert_eq!(recovered, keypair.address);
+
+        let other = hash_personal_message(b"goodbye");
+        assert_ne!(
+            recover_address(&other, &signature).unwrap(),
+            keypair.address
+        );
+    }
+
+    #[test]
+    fn test_signature_bytes_roundtrip() {
+        let keypair = EthKeyPair::random().unwrap();
+        let signature = sign_hash(&keypair.secret, &[7u8; 32]);
+
+        let bytes = signature.to_bytes();
+        assert_eq!(EthSignature::from_bytes(&bytes).unwrap(), signature);
+
+        let mut raw_v = bytes;
+        raw_v[64] -= 27;
+        assert_eq!(EthSignature::from_bytes(&raw_v).unwrap(), signature);
+
+        raw_v[64] = 5;
+        assert!(EthSignature::from_bytes(&raw_v).is_err());
+        assert!(EthSignature::from_bytes(&bytes[..64]).is_err());
+
+        // `v` is public, so a hand-built signature can hold anything
+        for v in [0, 26, 29] {
+            let invalid = EthSignature { v, ..signature };
+            assert!(matches!(
+                invalid.recovery_id(),
+                Err(CryptoError::InvalidSignature)
+            ));
+            assert!(invalid.to_recoverable().is_err());
+        }
+    }
+
+    #[test]
+    fn test_normalize_high_s() {
+        const ORDER: [u8; 32] = [
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xfe, 0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c,
+            0xd0, 0x36, 0x41, 0x41,
+        ];
+
+        let keypair = EthKeyPair::random().unwrap();
+        let hash = [9u8; 32];
+        let signature = sign_hash(&keypair.secret, &hash);
+        assert_eq!(signature.normalized().unwrap(), signature);
+
+        // s' = n - s, v' = v ^ 1 is the malleated twin of the same signature
+        let mut high_s = signature;
+        let mut borrow = 0i16;
+        for i in (0..32).rev() {
+            let diff = ORDER[i] as i16 - signature.s[i] as i16 - borrow;
+            high_s.s[i] = diff.rem_euclid(256) as u8;
+            borrow = if diff < 0 { 1 } else { 0 };
+        }
+        high_s.v = if signature.v == 27 { 28 } else { 27 };
+
+        assert_eq!(recover_address(&hash, &high_s).unwrap(), keypair.address);
+        assert_eq!(high_s.normalized().unwrap(), signature);
+    }
+}
@@ -0,0 +1,54 @@
+// Fixtures shared by the unit tests of the transfer, wallet and reserve
+// modules.
+
+use crate::keys::StealthKeys;
+use crate::pedersen::{generate_blinding, PedersenCommitment};
+use crate::reserve::RingMember;
+use crate::ring_signature::compute_key_image;
+use cryptography_types::wallet::OwnedOutput;
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+
+/// An output owned by `keys`, as if received earlier.
+pub fn owned_output(keys: &StealthKeys, amount: u64) -> OwnedOutput {
+    let offset = generate_blinding();
+    let blinding = generate_blinding();
+    let ring_pubkey = (keys.ring_secret.expose() + offset) * RISTRETTO_BASEPOINT_POINT;
+
+    OwnedOutput {
+        commitment: PedersenCommitment::new(amount, &blinding).to_bytes(),
+        amount,
+        blinding: blinding.to_bytes(),
+        stealth_address: [0x42u8; 20],
+        ring_pubkey: ring_pubkey.compress().to_bytes(),
+        ring_key_offset: offset.to_bytes(),
+        key_image: compute_key_image(&(keys.ring_secret.expose() + offset), &ring_pubkey)
+            .compress()
+            .to_bytes(),
+        height: Some(1),
+        spent: false,
+        spent_height: None,
+    }
+}
+
+/// Random ring keys to hide transfer inputs among.
+pub fn decoy_pool(size: usize) -> Vec<[u8; 32]> {
+    (0..size)
+        .map(|_| {
+            (generate_blinding() * RISTRETTO_BASEPOINT_POINT)
+                .compress()
+                .to_bytes()
+        })
+        .collect()
+}
+
+/// Random on-chain outputs to hide reserve proof inputs among.
+pub fn decoy_members(size: usize) -> Vec<RingMember> {
+    decoy_pool(size)
+        .into_iter()
+        .enumerate()
+        .map(|(i, ring_pubkey)| RingMember {
+            ring_pubkey,
+            commitment: PedersenCommitment::new(i as u64, &generate_blinding()).to_bytes(),
+        })
+        .collect()
+}
@@ -0,0 +1,410 @@
+// Builds a complete, proof-ready PrivateTransaction from wallet outputs.
+
+use crate::errors::{CryptoError, Result};
+use crate::ethereum::{generate_stealth_eth, stealth_shared_secret, EthAddress};
+use crate::keys::StealthMetaAddress;
+use crate::note::{encrypt_note, output_ring_pubkey};
+use crate::pedersen::{generate_blinding, PedersenCommitment};
+use crate::ring_signature::{compute_key_image, RingSignature};
+use crate::secret::Secret;
+use crate::utils::{canonical_scalar, decompress_point};
+use cryptography_types::{
+    signature::RingSignatureData,
+    transaction::{PrivateTransaction, TransactionBuilder, MAX_INPUTS},
+    wallet::OwnedOutput,
+};
+use curve25519_dalek::{constants::RISTRETTO_BASEPOINT_POINT, scalar::Scalar};
+use rand::{seq::SliceRandom, thread_rng, Rng};
+
+pub const DEFAULT_RING_SIZE: usize = 11;
+
+struct Recipient {
+    meta_address: StealthMetaAddress,
+    amount: u64,
+}
+
+/// Spends owned outputs to stealth recipients and public withdrawals.
+///
+/// Each input gets its own ring of `ring_size` keys: the input's one-time
+/// ring key at a random position, the rest drawn from the decoy pool. Output
+/// blindings are chosen so that they sum to the input blindings. Inputs are
+/// borrowed, so their secrets are not copied out of the wallet.
+pub struct TransferBuilder<'a> {
+    spend_key: Secret<Scalar>,
+    inputs: Vec<&'a OwnedOutput>,
+    recipients: Vec<Recipient>,
+    withdrawals: Vec<(EthAddress, u64)>,
+    fee: u64,
+    relayer: Option<EthAddress>,
+    decoys: Vec<[u8; 32]>,
+    ring_size: usize,
+    max_output_amount: Option<u64>,
+}
+
+impl<'a> TransferBuilder<'a> {
+    /// `spend_key` is the wallet's ring secret ([`crate::StealthKeys::ring_secret`]).
+    pub fn new(spend_key: &Secret<Scalar>) -> Self {
+        Self {
+            spend_key: Secret::new(*spend_key.expose()),
+            inputs: Vec::new(),
+            recipients: Vec::new(),
+            withdrawals: Vec::new(),
+            fee: 0,
+            relayer: None,
+            decoys: Vec::new(),
+            ring_size: DEFAULT_RING_SIZE,
+            max_output_amount: None,
+        }
+    }
+
+    pub fn add_input(mut self, output: &'a OwnedOutput) -> Self {
+        self.inputs.push(output);
+        self
+    }
+
+    pub fn add_recipient(mut self, meta_address: StealthMetaAddress, amount: u64) -> Self {
+        self.recipients.push(Recipient {
+            meta_address,
+            amount,
+        });
+        self
+    }
+
+    pub fn add_withdrawal(mut self, recipient: EthAddress, amount: u64) -> Self {
+        self.withdrawals.push((recipient, amount));
+        self
+    }
+
+    pub fn with_fee(mut self, fee: u64) -> Self {
+        self.fee = fee;
+        self
+    }
+
+    pub fn with_relayer(mut self, relayer: EthAddress) -> Self {
+        self.relayer = Some(relayer);
+        self
+    }
+
+    /// Ring keys of other on-chain outputs to use as decoys.
+    pub fn with_decoys(mut self, decoys: Vec<[u8; 32]>) -> Self {
+        self.decoys = decoys;
+        self
+    }
+
+    pub fn with_ring_size(mut self, ring_size: usize) -> Self {
+        self.ring_size = ring_size;
+        self
+    }
+
+    /// Refuses to build if any output or withdrawal is above `max`. This is
+    /// a policy of the sending wallet only: neither `validate()` nor the
+    /// guest enforce it.
+    pub fn with_max_output_amount(mut self, max: u64) -> Self {
+        self.max_output_amount = Some(max);
+        self
+    }
+
+    pub fn build(self) -> Result<PrivateTransaction> {
+        if self.inputs.is_empty() {
+            return Err(CryptoError::InvalidInput("Transfer has no inputs".into()));
+        }
+        if self.inputs.len() > MAX_INPUTS {
+            return Err(CryptoError::TooManyInputs {
+                count: self.inputs.len(),
+                max: MAX_INPUTS,
+            });
+        }
+        if self.ring_size == 0 {
+            return Err(CryptoError::InvalidInput(
+                "Ring size must be at least 1".into(),
+            ));
+        }
+
+        let mut rng = thread_rng();
+
+        let input_keys: Vec<[u8; 32]> = self.inputs.iter().map(|i| i.ring_pubkey).collect();
+        let mut decoys: Vec<[u8; 32]> = Vec::new();
+        for decoy in &self.decoys {
+            if !input_keys.contains(decoy) && !decoys.contains(decoy) {
+                decoys.push(*decoy);
+            }
+        }
+
+        let needed = (self.ring_size - 1) * self.inputs.len();
+        if decoys.len() < needed {
+            return Err(CryptoError::InsufficientDecoys {
+                needed,
+                available: decoys.len(),
+            });
+        }
+        decoys.shuffle(&mut rng);
+
+        let mut builder = TransactionBuilder::new();
+        let mut input_blinding_sum = Scalar::ZERO;
+        let mut rings = Vec::new();
+        let mut signers = Vec::new();
+        let mut secret_indices = Vec::new();
+
+        let per_ring = self.ring_size - 1;
+
+        for (i, input) in self.inputs.iter().enumerate() {
+            let ring_decoys = &decoys[i * per_ring..(i + 1) * per_ring];
+
+            if input.spent {
+                return Err(CryptoError::InvalidInput("Input is already spent".into()));
+            }
+
+            let blinding = canonical_scalar(&input.blinding)?;
+            if !PedersenCommitment::from_bytes(&input.commitment)?.verify(input.amount, &blinding) {
+                return Err(CryptoError::CommitmentVerificationFailed);
+            }
+            input_blinding_sum += blinding;
+
+            let secret_key =
+                Secret::new(self.spend_key.expose() + canonical_scalar(&input.ring_key_offset)?);
+            let public_key = decompress_point(&input.ring_pubkey)?;
+            if secret_key.expose() * RISTRETTO_BASEPOINT_POINT != public_key {
+                return Err(CryptoError::InvalidInput(
+                    "Input is not spendable with this key".into(),
+                ));
+            }
+
+            let secret_index = rng.gen_range(0..self.ring_size);
+            let mut ring: Vec<[u8; 32]> = ring_decoys.to_vec();
+            ring.insert(secret_index, input.ring_pubkey);
+
+            let ring_points = ring
+                .iter()
+                .map(decompress_point)
+                .collect::<Result<Vec<_>>>()?;
+            let key_image = compute_key_image(secret_key.expose(), &public_key);
+
+            builder = builder.add_input(
+                input.commitment,
+                key_image.compress().to_bytes(),
+                input.amount,
+                input.blinding,
+            );
+            signers.push((secret_key, ring_points));
+            rings.push(ring);
+            secret_indices.push(secret_index as u32);
+        }
+
+        let mut output_blinding_sum = Scalar::ZERO;
+        let last = self.recipients.len().saturating_sub(1);
+
+        for (i, recipient) in self.recipients.iter().enumerate() {
+            let meta = &recipient.meta_address;
+            let (stealth, ephemeral_secret) =
+                generate_stealth_eth(&meta.view_pubkey, &meta.spend_pubkey)?;
+            let shared_secret = stealth_shared_secret(&meta.view_pubkey, &ephemeral_secret)?;
+
+            let blinding = if i == last {
+                input_blinding_sum - output_blinding_sum
+            } else {
+                generate_blinding()
+            };
+            output_blinding_sum += blinding;
+
+            let commitment = PedersenCommitment::new(recipient.amount, &blinding);
+            let ring_pubkey = output_ring_pubkey(&meta.ring_pubkey, &shared_secret);
+
+            builder = builder
+                .add_output(
+                    commitment.to_bytes(),
+                    stealth.stealth_address,
+                    stealth.ephemeral_pubkey,
+                    recipient.amount,
+                    blinding.to_bytes(),
+                )
+                .add_output_note(encrypt_note(
+                    &shared_secret,
+                    &ring_pubkey,
+                    recipient.amount,
+                    &blinding,
+                ));
+        }
+
+        for (recipient, amount) in &self.withdrawals {
+            builder = builder.add_withdrawal(*recipient, *amount);
+        }
+        builder = builder.with_fee(self.fee);
+        if let Some(relayer) = self.relayer {
+            builder = builder.with_relayer(relayer);
+        }
+        if let Some(max) = self.max_output_amount {
+            builder = builder.with_max_output_amount(max);
+        }
+
+        // Signatures are not part of the txid, so sign once everything else is fixed
+        let mut tx = builder.build(rings, Vec::new(), secret_indices)?;
+        let txid = tx.public.txid()?;
+
+        for ((secret_key, ring_points), secret_index) in
+            signers.iter().zip(&tx.witness.secret_indices)
+        {
+            let signature = RingSignature::sign(
+                &txid,
+                secret_key.expose(),
+                *secret_index as usize,
+                ring_points,
+            );
+            tx.public
+                .ring_signatures
+                .push(RingSignatureData::from(&signature));
+        }
+
+        Ok(tx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ethereum::scan_stealth_eth;
+    use crate::keys::StealthKeys;
+    use crate::note::{decrypt_note, output_key_offset};
+    use crate::ring_signature::verify_ring;
+    use crate::test_utils::{decoy_pool, owned_output};
+    use crate::StealthAddressEth;
+    use cryptography_types::errors::BalanceError;
+    use curve25519_dalek::ristretto::RistrettoPoint;
+
+    #[test]
+    fn test_build_transfer() {
+        let sender = StealthKeys::from_seed(&[1u8; 64], 0).unwrap();
+        let recipient = StealthKeys::from_seed(&[2u8; 64], 0).unwrap();
+
+        let tx = TransferBuilder::new(&sender.ring_secret)
+            .add_input(&owned_output(&sender, 70))
+            .add_input(&owned_output(&sender, 50))
+            .add_recipient(recipient.meta_address(), 100)
+            .add_recipient(sender.meta_address(), 15)
+            .with_fee(5)
+            .with_decoys(decoy_pool(20))
+            .with_ring_size(5)
+            .build()
+            .unwrap();
+
+        assert_eq!(tx.validate(), Ok(()));
+
+        let public = &tx.public;
+        assert_eq!(public.rings.len(), 2);
+        assert_eq!(public.output_notes.len(), 2);
+
+        // Every ring signature verifies against its own ring
+        for (i, ring) in public.rings.iter().enumerate() {
+            assert_eq!(ring.len(), 5);
+            let ring_points: Vec<RistrettoPoint> =
+                ring.iter().map(|k| decompress_point(k).unwrap()).collect();
+            let signature = RingSignature::try_from(&public.ring_signatures[i]).unwrap();
+            assert_eq!(
+                signature.key_image.compress().to_bytes(),
+                public.key_images[i]
+            );
+            assert!(verify_ring(
+                &signature,
+                &public.txid().unwrap(),
+                &ring_points
+            ));
+        }
+
+        // Commitments open, and blindings balance homomorphically
+        let mut input_sum = RistrettoPoint::default();
+        for c in &public.input_commitments {
+            input_sum += decompress_point(&c.commitment).unwrap();
+        }
+        let mut output_sum = RistrettoPoint::default();
+        for (i, c) in public.output_commitments.iter().enumerate() {
+            let blinding = canonical_scalar(&tx.witness.output_blindings[i]).unwrap();
+            let commitment = PedersenCommitment::from_bytes(&c.commitment).unwrap();
+            assert!(commitment.verify(tx.witness.output_amounts[i], &blinding));
+            output_sum += commitment.point;
+        }
+        assert_eq!(
+            input_sum - output_sum,
+            Scalar::from(public.fee) * RISTRETTO_BASEPOINT_POINT
+        );
+
+        // The recipient finds the output and can spend it
+        let stealth = &public.stealth_addresses[0];
+        let hs = scan_stealth_eth(
+            &StealthAddressEth {
+                ephemeral_pubkey: stealth.ephemeral_pubkey.clone(),
+                stealth_address: stealth.stealth_address,
+            },
+            &recipient.view.secret,
+            &recipient.spend.public,
+        )
+        .unwrap()
+        .unwrap();
+        let shared_secret = hs.secret_bytes();
+
+        let note = &public.output_notes[0];
+        let (amount, blinding) = decrypt_note(&shared_secret, note).unwrap();
+        assert_eq!(amount, 100);
+        assert!(
+            PedersenCommitment::from_bytes(&public.output_commitments[0].commitment)
+                .unwrap()
+                .verify(amount, &blinding)
+        );
+
+        let one_time_secret = recipient.ring_secret.expose() + output_key_offset(&shared_secret);
+        assert_eq!(
+            (one_time_secret * RISTRETTO_BASEPOINT_POINT)
+                .compress()
+                .to_bytes(),
+            note.ring_pubkey
+        );
+    }
+
+    #[test]
+    fn test_build_errors() {
+        let sender = StealthKeys::from_seed(&[1u8; 64], 0).unwrap();
+        let recipient = StealthKeys::from_seed(&[2u8; 64], 0).unwrap();
+
+        let few_decoys = TransferBuilder::new(&sender.ring_secret)
+            .add_input(&owned_output(&sender, 100))
+            .add_recipient(recipient.meta_address(), 100)
+            .with_decoys(decoy_pool(3))
+            .with_ring_size(5)
+            .build();
+        assert!(matches!(
+            few_decoys,
+            Err(CryptoError::InsufficientDecoys {
+                needed: 4,
+                available: 3
+            })
+        ));
+
+        let unbalanced = TransferBuilder::new(&sender.ring_secret)
+            .add_input(&owned_output(&sender, 100))
+            .add_recipient(recipient.meta_address(), 101)
+            .with_decoys(decoy_pool(10))
+            .build();
+        assert!(matches!(unbalanced, Err(CryptoError::Balance(_))));
+
+        let not_owned = TransferBuilder::new(&recipient.ring_secret)
+            .add_input(&owned_output(&sender, 100))
+            .add_recipient(recipient.meta_address(), 100)
+            .with_decoys(decoy_pool(10))
+            .build();
+        assert!(matches!(not_owned, Err(CryptoError::InvalidInput(_))));
+
+        let too_large = TransferBuilder::new(&sender.ring_secret)
+            .add_input(&owned_output(&sender, 100))
+            .add_recipient(recipient.meta_address(), 60)
+            .add_withdrawal([0x42u8; 20], 40)
+            .with_decoys(decoy_pool(10))
+            .with_max_output_amount(50)
+            .build();
+        assert!(matches!(
+            too_large,
+            Err(CryptoError::Balance(BalanceError::OutputAmountTooLarge {
+                index: 0,
+                amount: 60,
+                max: 50
+            }))
+        ));
+    }
+}
@@ -1,3 +1,8 @@
+use crate::errors::{CryptoError, Result};
+use curve25519_dalek::{
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+};
 use sha2::{Digest, Sha256};
 use sha3::Keccak256;
 
@@ -28,7 +33,19 @@ pub fn random_bytes<const N: usize>() -> [u8; N] {
     bytes
 }
 
-pub fn from_hex(s: &str) -> Result<Vec<u8>, String> {
+/// Rejects encodings that are not reduced mod the group order.
+pub fn canonical_scalar(bytes: &[u8; 32]) -> Result<Scalar> {
+    Option::from(Scalar::from_canonical_bytes(*bytes)).ok_or(CryptoError::InvalidScalar)
+}
+
+/// Ristretto decompression only accepts canonical encodings.
+pub fn decompress_point(bytes: &[u8; 32]) -> Result<RistrettoPoint> {
+    CompressedRistretto(*bytes)
+        .decompress()
+        .ok_or(CryptoError::InvalidRisettoPoints)
+}
+
+pub fn from_hex(s: &str) -> std::result::Result<Vec<u8>, String> {
     let s = s.strip_prefix("0x").unwrap_or(s);
     hex::decode(s).map_err(|e| format!("Invalid hex: {}", e))
 }
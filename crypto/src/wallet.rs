@@ -0,0 +1,702 @@
+// Tracks the outputs a stealth wallet owns. Outputs are found by scanning
+// stealth addresses and output notes; spends are detected by matching the key
+// images of published transactions against the key image precomputed for
+// every owned output.
+//
+// A view-only wallet holds just a viewing key. It finds and opens incoming
+// outputs the same way, but cannot compute key images and so never sees spends.
+
+use crate::coin_selection::{CoinSelector, Selection, SelectionStrategy};
+use crate::errors::{CryptoError, Result};
+use crate::ethereum::{scan_stealth_eth, stealth_shared_secret, StealthAddressEth};
+use crate::keys::{StealthKeys, StealthMetaAddress, ViewingKey};
+use crate::note::{decrypt_note, output_key_offset, output_ring_pubkey};
+use crate::pedersen::PedersenCommitment;
+use crate::reserve::{ReserveProof, RingMember};
+use crate::ring_signature::compute_key_image;
+use crate::transfer::{TransferBuilder, DEFAULT_RING_SIZE};
+use cryptography_types::{
+    commitment::CommitmentData,
+    errors::BalanceError,
+    stealth::{OutputNote, StealthAddressData},
+    transaction::{checked_sum, PublicTransaction},
+    wallet::{HistoryEntry, HistoryKind, OwnedOutput, WalletState},
+};
+use secp256k1::PublicKey;
+use std::collections::HashMap;
+use std::mem;
+
+pub struct Wallet {
+    /// `None` for a view-only wallet.
+    keys: Option<StealthKeys>,
+    viewing_key: ViewingKey,
+    outputs: Vec<OwnedOutput>,
+    /// Key images seen on-chain or in the mempool, with their inclusion height.
+    /// Kept so an output ingested after its spend is still marked spent.
+    seen_key_images: HashMap<[u8; 32], Option<u64>>,
+}
+
+impl Wallet {
+    pub fn new(keys: StealthKeys) -> Self {
+        Self {
+            viewing_key: keys.viewing_key(),
+            keys: Some(keys),
+            outputs: Vec::new(),
+            seen_key_images: HashMap::new(),
+        }
+    }
+
+    pub fn view_only(viewing_key: ViewingKey) -> Self {
+        Self {
+            keys: None,
+            viewing_key,
+            outputs: Vec::new(),
+            seen_key_images: HashMap::new(),
+        }
+    }
+
+    pub fn from_state(mut state: WalletState) -> Result<Self> {
+        let mut wallet = if state.is_view_only() {
+            Self::view_only(ViewingKey::from_wallet_state(&state)?)
+        } else {
+            Self::new(StealthKeys::from_wallet_state(&state)?)
+        };
+        for output in &state.outputs {
+            if output.spent {
+                wallet
+                    .seen_key_images
+                    .insert(output.key_image, output.spent_height);
+            }
+        }
+        wallet.outputs = mem::take(&mut state.outputs);
+        Ok(wallet)
+    }
+
+    pub fn to_state(mut self) -> WalletState {
+        let mut state = match &self.keys {
+            Some(keys) => keys.to_wallet_state(),
+            None => self.viewing_key.to_wallet_state(),
+        };
+        state.outputs = mem::take(&mut self.outputs);
+        state
+    }
+
+    pub fn is_view_only(&self) -> bool {
+        self.keys.is_none()
+    }
+
+    /// Fails with [`CryptoError::ViewOnly`] for a view-only wallet.
+    pub fn keys(&self) -> Result<&StealthKeys> {
+        self.keys.as_ref().ok_or(CryptoError::ViewOnly)
+    }
+
+    pub fn viewing_key(&self) -> &ViewingKey {
+        &self.viewing_key
+    }
+
+    pub fn meta_address(&self) -> StealthMetaAddress {
+        self.viewing_key.meta_address()
+    }
+
+    /// A builder that signs with this wallet's ring secret.
+    pub fn transfer(&self) -> Result<TransferBuilder<'_>> {
+        Ok(TransferBuilder::new(&self.keys()?.ring_secret))
+    }
+
+    pub fn outputs(&self) -> &[OwnedOutput] {
+        &self.outputs
+    }
+
+    /// Confirmed outputs that have not been spent, ready for `TransferBuilder`.
+    pub fn spendable(&self) -> impl Iterator<Item = &OwnedOutput> {
+        self.outputs
+            .iter()
+            .filter(|o| o.is_confirmed() && o.is_unspent())
+    }
+
+    /// Sum of [`Self::spendable`]. An output leaves the balance as soon as its
+    /// key image shows up, even if the spend is still pending (see
+    /// [`Self::drop_pending`] for spends that never confirm). A view-only
+    /// wallet never sees spends, so this is everything it received. Fails if
+    /// the total does not fit in a `u64`.
+    pub fn balance(&self) -> Result<u64> {
+        checked_sum(self.spendable().map(|o| o.amount))
+            .ok_or(CryptoError::Balance(BalanceError::AmountOverflow))
+    }
+
+    /// Picks spendable outputs to pay `amount`; apply the result to a
+    /// `TransferBuilder` with [`Selection::apply`].
+    pub fn select_coins(
+        &self,
+        amount: u64,
+        selector: &CoinSelector,
+        strategy: SelectionStrategy,
+    ) -> Result<Selection<'_>> {
+        self.keys()?;
+        selector.select(self.spendable(), amount, strategy)
+    }
+
+    /// Proves that [`Self::spendable`] holds at least `minimum`, hiding each
+    /// output in a ring of [`DEFAULT_RING_SIZE`] members from `decoys`.
+    pub fn prove_reserves(
+        &self,
+        minimum: u64,
+        decoys: &[RingMember],
+        context: &[u8],
+    ) -> Result<ReserveProof> {
+        ReserveProof::prove(
+            self.keys()?.ring_secret.expose(),
+            self.spendable(),
+            decoys,
+            DEFAULT_RING_SIZE,
+            minimum,
+            context,
+        )
+    }
+
+    /// Incoming amounts that are not confirmed yet. Fails on overflow, like
+    /// [`Self::balance`].
+    pub fn pending(&self) -> Result<u64> {
+        checked_sum(
+            self.outputs
+                .iter()
+                .filter(|o| !o.is_confirmed() && o.is_unspent())
+                .map(|o| o.amount),
+        )
+        .ok_or(CryptoError::Balance(BalanceError::AmountOverflow))
+    }
+
+    /// Every receive and spend, oldest first; pending entries come last.
+    pub fn history(&self) -> Vec<HistoryEntry> {
+        let mut history = Vec::new();
+        for output in &self.outputs {
+            history.push(HistoryEntry {
+                kind: HistoryKind::Received,
+                amount: output.amount,
+                commitment: output.commitment,
+                height: output.height,
+            });
+            if output.spent {
+                history.push(HistoryEntry {
+                    kind: HistoryKind::Spent,
+                    amount: output.amount,
+                    commitment: output.commitment,
+                    height: output.spent_height,
+                });
+            }
+        }
+
+        history.sort_by_key(|entry| entry.height.unwrap_or(u64::MAX));
+        history
+    }
+
+    /// Applies a published transaction: marks owned outputs whose key image it
+    /// reveals as spent and picks up outputs paid to this wallet. `height` is
+    /// `None` for mempool transactions. Returns the entries it added.
+    pub fn ingest_transaction(
+        &mut self,
+        tx: &PublicTransaction,
+        height: Option<u64>,
+    ) -> Result<Vec<HistoryEntry>> {
+        let mut entries = Vec::new();
+
+        for key_image in &tx.key_images {
+            entries.extend(self.observe_key_image(key_image, height));
+        }
+
+        for (i, note) in tx.output_notes.iter().enumerate() {
+            let (Some(commitment), Some(stealth)) =
+                (tx.output_commitments.get(i), tx.stealth_addresses.get(i))
+            else {
+                break;
+            };
+
+            let known = self
+                .outputs
+                .iter()
+                .any(|o| o.commitment == commitment.commitment);
+            // A sender can attach a note that doesn't open its commitment; that
+            // output is unspendable, but must not stop the rest being scanned
+            let Ok(output) = self.ingest_output(commitment, stealth, note, height) else {
+                continue;
+            };
+            if let Some(output) = output {
+                if !known {
+                    entries.push(HistoryEntry {
+                        kind: HistoryKind::Received,
+                        amount: output.amount,
+                        commitment: output.commitment,
+                        height,
+                    });
+                }
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// Tracks the output if it is addressed to this wallet. Ingesting an output
+    /// again (e.g. once its transaction confirms) only updates its height.
+    ///
+    /// Fails if the stealth address is ours but the note does not open the
+    /// commitment or derive our ring key; such an output cannot be spent.
+    pub fn ingest_output(
+        &mut self,
+        commitment: &CommitmentData,
+        stealth: &StealthAddressData,
+        note: &OutputNote,
+        height: Option<u64>,
+    ) -> Result<Option<&OwnedOutput>> {
+        // Someone else's malformed output is simply not ours
+        let Ok(stealth) = StealthAddressEth::try_from(stealth.clone()) else {
+            return Ok(None);
+        };
+        let viewing_key = &self.viewing_key;
+        if scan_stealth_eth(
+            &stealth,
+            &viewing_key.view.secret,
+            &viewing_key.spend_pubkey,
+        )?
+        .is_none()
+        {
+            return Ok(None);
+        }
+
+        if let Some(index) = self
+            .outputs
+            .iter()
+            .position(|o| o.commitment == commitment.commitment)
+        {
+            let output = &mut self.outputs[index];
+            if height.is_some() {
+                output.height = height;
+            }
+            return Ok(Some(&self.outputs[index]));
+        }
+
+        let ephemeral_pubkey = PublicKey::from_slice(&stealth.ephemeral_pubkey)
+            .map_err(|_| CryptoError::InvalidPublicKey)?;
+        let shared_secret = stealth_shared_secret(&ephemeral_pubkey, &viewing_key.view.secret)?;
+
+        let (amount, blinding) = decrypt_note(&shared_secret, note)?;
+        if !PedersenCommitment::try_from(*commitment)?.verify(amount, &blinding) {
+            return Err(CryptoError::CommitmentVerificationFailed);
+        }
+
+        let ring_pubkey = output_ring_pubkey(&viewing_key.ring_pubkey, &shared_secret);
+        if ring_pubkey.compress().to_bytes() != note.ring_pubkey {
+            return Err(CryptoError::InvalidInput(
+                "Note ring key does not belong to this wallet".into(),
+            ));
+        }
+
+        let offset = output_key_offset(&shared_secret);
+        let (key_image, spent_height) = match &self.keys {
+            Some(keys) => {
+                let key_image =
+                    compute_key_image(&(keys.ring_secret.expose() + offset), &ring_pubkey)
+                        .compress()
+                        .to_bytes();
+                (key_image, self.seen_key_images.get(&key_image).copied())
+            }
+            None => ([0u8; 32], None),
+        };
+
+        self.outputs.push(OwnedOutput {
+            commitment: commitment.commitment,
+            amount,
+            blinding: blinding.to_bytes(),
+            stealth_address: stealth.stealth_address,
+            ring_pubkey: note.ring_pubkey,
+            ring_key_offset: offset.to_bytes(),
+            key_image,
+            height,
+            spent: spent_height.is_some(),
+            spent_height: spent_height.flatten(),
+        });
+        Ok(self.outputs.last())
+    }
+
+    /// Forgets everything only seen in the mempool: unconfirmed outputs go
+    /// away and outputs with an unconfirmed spend become spendable again. Call
+    /// it when pending transactions are dropped or replaced, then ingest the
+    /// current mempool again.
+    pub fn drop_pending(&mut self) {
+        self.seen_key_images.retain(|_, height| height.is_some());
+        self.outputs.retain(|o| o.is_confirmed());
+        for output in &mut self.outputs {
+            if output.spent && output.spent_height.is_none() {
+                output.spent = false;
+            }
+        }
+    }
+
+    /// Records a key image seen on-chain (`Some(height)`) or in the mempool.
+    /// Returns a history entry if it newly spends one of our outputs.
+    pub fn observe_key_image(
+        &mut self,
+        key_image: &[u8; 32],
+        height: Option<u64>,
+    ) -> Option<HistoryEntry> {
+        let seen = self.seen_key_images.entry(*key_image).or_insert(height);
+        if height.is_some() {
+            *seen = height;
+        }
+        // View-only outputs have no key image to match
+        if self.is_view_only() {
+            return None;
+        }
+
+        let output = self
+            .outputs
+            .iter_mut()
+            .find(|o| &o.key_image == key_image)?;
+        if height.is_some() {
+            output.spent_height = height;
+        }
+        if output.spent {
+            return None;
+        }
+
+        output.mark_spent();
+        Some(HistoryEntry {
+            kind: HistoryKind::Spent,
+            amount: output.amount,
+            commitment: output.commitment,
+            height,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{decoy_pool, owned_output};
+    use std::collections::HashSet;
+
+    fn pay(from: &StealthKeys, to: StealthMetaAddress, amount: u64) -> PublicTransaction {
+        TransferBuilder::new(&from.ring_secret)
+            .add_input(&owned_output(from, amount + 10))
+            .add_recipient(to, amount)
+            .add_recipient(from.meta_address(), 9)
+            .with_fee(1)
+            .with_decoys(decoy_pool(10))
+            .with_ring_size(4)
+            .build()
+            .unwrap()
+            .public
+    }
+
+    #[test]
+    fn test_receive_pending_then_confirmed() {
+        let sender = StealthKeys::from_seed(&[1u8; 64], 0).unwrap();
+        let mut wallet = Wallet::new(StealthKeys::from_seed(&[2u8; 64], 0).unwrap());
+        let tx = pay(&sender, wallet.meta_address(), 100);
+
+        let entries = wallet.ingest_transaction(&tx, None).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].kind, HistoryKind::Received);
+        assert_eq!(wallet.pending().unwrap(), 100);
+        assert_eq!(wallet.balance().unwrap(), 0);
+
+        // Confirmation updates the output instead of adding another
+        let entries = wallet.ingest_transaction(&tx, Some(10)).unwrap();
+        assert!(entries.is_empty());
+        assert_eq!(wallet.outputs().len(), 1);
+        assert_eq!(wallet.pending().unwrap(), 0);
+        assert_eq!(wallet.balance().unwrap(), 100);
+        assert_eq!(wallet.outputs()[0].height, Some(10));
+    }
+
+    #[test]
+    fn test_ignores_other_outputs() {
+        let sender = StealthKeys::from_seed(&[1u8; 64], 0).unwrap();
+        let other = StealthKeys::from_seed(&[3u8; 64], 0).unwrap();
+        let mut wallet = Wallet::new(StealthKeys::from_seed(&[2u8; 64], 0).unwrap());
+
+        let tx = pay(&sender, other.meta_address(), 100);
+        assert!(wallet.ingest_transaction(&tx, Some(5)).unwrap().is_empty());
+        assert!(wallet.outputs().is_empty());
+    }
+
+    #[test]
+    fn test_spend_detected_by_key_image() {
+        let sender = StealthKeys::from_seed(&[1u8; 64], 0).unwrap();
+        let mut wallet = Wallet::new(StealthKeys::from_seed(&[2u8; 64], 0).unwrap());
+        wallet
+            .ingest_transaction(&pay(&sender, wallet.meta_address(), 100), Some(10))
+            .unwrap();
+
+        // Spend the received output back to the sender with change to self
+        let spend = wallet
+            .transfer()
+            .unwrap()
+            .add_input(wallet.spendable().next().unwrap())
+            .add_recipient(sender.meta_address(), 60)
+            .add_recipient(wallet.meta_address(), 38)
+            .with_fee(2)
+            .with_decoys(decoy_pool(10))
+            .with_ring_size(4)
+            .build()
+            .unwrap()
+            .public;
+        assert_eq!(spend.key_images[0], wallet.outputs()[0].key_image);
+
+        let entries = wallet.ingest_transaction(&spend, None).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].kind, HistoryKind::Spent);
+        assert_eq!(entries[0].amount, 100);
+        assert_eq!(entries[1].amount, 38);
+        assert_eq!(wallet.balance().unwrap(), 0);
+        assert_eq!(wallet.pending().unwrap(), 38);
+
+        wallet.ingest_transaction(&spend, Some(11)).unwrap();
+        assert_eq!(wallet.balance().unwrap(), 38);
+        assert_eq!(wallet.outputs()[0].spent_height, Some(11));
+
+        let history = wallet.history();
+        let kinds: Vec<_> = history.iter().map(|e| (e.kind, e.height)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (HistoryKind::Received, Some(10)),
+                (HistoryKind::Spent, Some(11)),
+                (HistoryKind::Received, Some(11)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bad_note_does_not_block_scanning() {
+        let sender = StealthKeys::from_seed(&[1u8; 64], 0).unwrap();
+        let mut wallet = Wallet::new(StealthKeys::from_seed(&[2u8; 64], 0).unwrap());
+        let mut tx = TransferBuilder::new(&sender.ring_secret)
+            .add_input(&owned_output(&sender, 100))
+            .add_recipient(wallet.meta_address(), 60)
+            .add_recipient(wallet.meta_address(), 39)
+            .with_fee(1)
+            .with_decoys(decoy_pool(10))
+            .with_ring_size(4)
+            .build()
+            .unwrap()
+            .public;
+        tx.output_notes[0].encrypted_amount[0] ^= 1;
+
+        let entries = wallet.ingest_transaction(&tx, Some(10)).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].amount, 39);
+        assert_eq!(wallet.balance().unwrap(), 39);
+    }
+
+    #[test]
+    fn test_balance_overflow() {
+        let sender = StealthKeys::from_seed(&[1u8; 64], 0).unwrap();
+        let mut wallet = Wallet::new(StealthKeys::from_seed(&[2u8; 64], 0).unwrap());
+        for height in [None, None, Some(10), Some(11)] {
+            wallet
+                .ingest_transaction(&pay(&sender, wallet.meta_address(), 100), height)
+                .unwrap();
+        }
+
+        let mut state = wallet.to_state();
+        for output in &mut state.outputs {
+            output.amount = u64::MAX;
+        }
+        let wallet = Wallet::from_state(state).unwrap();
+        assert!(matches!(
+            wallet.balance(),
+            Err(CryptoError::Balance(BalanceError::AmountOverflow))
+        ));
+        assert!(matches!(
+            wallet.pending(),
+            Err(CryptoError::Balance(BalanceError::AmountOverflow))
+        ));
+    }
+
+    #[test]
+    fn test_drop_pending_spend() {
+        let sender = StealthKeys::from_seed(&[1u8; 64], 0).unwrap();
+        let mut wallet = Wallet::new(StealthKeys::from_seed(&[2u8; 64], 0).unwrap());
+        wallet
+            .ingest_transaction(&pay(&sender, wallet.meta_address(), 100), Some(10))
+            .unwrap();
+
+        let spend = wallet
+            .transfer()
+            .unwrap()
+            .add_input(wallet.spendable().next().unwrap())
+            .add_recipient(sender.meta_address(), 60)
+            .add_recipient(wallet.meta_address(), 38)
+            .with_fee(2)
+            .with_decoys(decoy_pool(10))
+            .with_ring_size(4)
+            .build()
+            .unwrap()
+            .public;
+        wallet.ingest_transaction(&spend, None).unwrap();
+        assert_eq!(wallet.balance().unwrap(), 0);
+        assert_eq!(wallet.pending().unwrap(), 38);
+
+        // The spend never confirms
+        wallet.drop_pending();
+        assert_eq!(wallet.balance().unwrap(), 100);
+        assert_eq!(wallet.pending().unwrap(), 0);
+        assert_eq!(wallet.outputs().len(), 1);
+        assert_eq!(wallet.history().len(), 1);
+
+        // Seeing it again, now confirmed, spends the output as usual
+        wallet.ingest_transaction(&spend, Some(11)).unwrap();
+        assert_eq!(wallet.balance().unwrap(), 38);
+        assert_eq!(wallet.outputs()[0].spent_height, Some(11));
+    }
+
+    #[test]
+    fn test_key_image_seen_before_output() {
+        let sender = StealthKeys::from_seed(&[1u8; 64], 0).unwrap();
+        let mut scanner = Wallet::new(StealthKeys::from_seed(&[2u8; 64], 0).unwrap());
+        let tx = pay(&sender, scanner.meta_address(), 100);
+        scanner.ingest_transaction(&tx, Some(10)).unwrap();
+        let key_image = scanner.outputs()[0].key_image;
+
+        // A fresh restore that sees the spend first still ends up consistent
+        let mut restored = Wallet::new(StealthKeys::from_seed(&[2u8; 64], 0).unwrap());
+        assert!(restored.observe_key_image(&key_image, Some(12)).is_none());
+        restored.ingest_transaction(&tx, Some(10)).unwrap();
+
+        let output = &restored.outputs()[0];
+        assert!(output.spent);
+        assert_eq!(output.spent_height, Some(12));
+        assert_eq!(restored.balance().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_state_roundtrip() {
+        let sender = StealthKeys::from_seed(&[1u8; 64], 0).unwrap();
+        let mut wallet = Wallet::new(StealthKeys::from_seed(&[2u8; 64], 0).unwrap());
+        wallet
+            .ingest_transaction(&pay(&sender, wallet.meta_address(), 100), Some(10))
+            .unwrap();
+
+        let history = wallet.history();
+        let restored = Wallet::from_state(wallet.to_state()).unwrap();
+        assert_eq!(restored.balance().unwrap(), 100);
+        assert_eq!(restored.history(), history);
+    }
+
+    #[test]
+    fn test_view_only_wallet() {
+        let sender = StealthKeys::from_seed(&[1u8; 64], 0).unwrap();
+        let mut wallet = Wallet::new(StealthKeys::from_seed(&[2u8; 64], 0).unwrap());
+        let exported = wallet.viewing_key().export();
+        let mut watcher = Wallet::view_only(ViewingKey::import(&exported).unwrap());
+        assert_eq!(watcher.meta_address(), wallet.meta_address());
+
+        let tx = pay(&sender, wallet.meta_address(), 100);
+        wallet.ingest_transaction(&tx, Some(10)).unwrap();
+        let entries = watcher.ingest_transaction(&tx, Some(10)).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(watcher.balance().unwrap(), 100);
+
+        let output = &watcher.outputs()[0];
+        assert_eq!(output.blinding, wallet.outputs()[0].blinding);
+        assert_eq!(output.key_image, [0u8; 32]);
+
+        assert!(matches!(watcher.keys(), Err(CryptoError::ViewOnly)));
+        assert!(matches!(watcher.transfer(), Err(CryptoError::ViewOnly)));
+        assert!(matches!(
+            watcher.select_coins(50, &CoinSelector::new(), SelectionStrategy::LargestFirst),
+            Err(CryptoError::ViewOnly)
+        ));
+
+        // Spends are invisible without key images
+        let spend = wallet
+            .transfer()
+            .unwrap()
+            .add_input(wallet.spendable().next().unwrap())
+            .add_recipient(sender.meta_address(), 99)
+            .with_fee(1)
+            .with_decoys(decoy_pool(10))
+            .with_ring_size(4)
+            .build()
+            .unwrap()
+            .public;
+        watcher.ingest_transaction(&spend, Some(11)).unwrap();
+        assert_eq!(watcher.balance().unwrap(), 100);
+
+        let history = watcher.history();
+        let restored = Wallet::from_state(watcher.to_state()).unwrap();
+        assert!(restored.is_view_only());
+        assert_eq!(restored.history(), history);
+    }
+
+    #[test]
+    fn test_select_coins_and_spend() {
+        let sender = StealthKeys::from_seed(&[1u8; 64], 0).unwrap();
+        let recipient = StealthKeys::from_seed(&[3u8; 64], 0).unwrap();
+        let mut wallet = Wallet::new(StealthKeys::from_seed(&[2u8; 64], 0).unwrap());
+        for (height, amount) in [(10, 100), (11, 40), (12, 25)] {
+            wallet
+                .ingest_transaction(&pay(&sender, wallet.meta_address(), amount), Some(height))
+                .unwrap();
+        }
+
+        let selector = CoinSelector::new().with_base_fee(2).with_fee_per_input(1);
+        let selection = wallet
+            .select_coins(120, &selector, SelectionStrategy::LargestFirst)
+            .unwrap();
+        assert_eq!(selection.inputs.len(), 2);
+        assert_eq!(selection.fee, 4);
+        assert_eq!(selection.change, 16);
+
+        let builder = wallet
+            .transfer()
+            .unwrap()
+            .add_recipient(recipient.meta_address(), 120)
+            .with_decoys(decoy_pool(10))
+            .with_ring_size(4);
+        let tx = selection
+            .apply(builder, wallet.meta_address())
+            .build()
+            .unwrap();
+        assert_eq!(tx.validate(), Ok(()));
+
+        wallet.ingest_transaction(&tx.public, Some(13)).unwrap();
+        assert_eq!(wallet.balance().unwrap(), 25 + 16);
+    }
+
+    #[test]
+    fn test_prove_reserves() {
+        let sender = StealthKeys::from_seed(&[1u8; 64], 0).unwrap();
+        let mut wallet = Wallet::new(StealthKeys::from_seed(&[2u8; 64], 0).unwrap());
+        for (height, amount) in [(10, 70), (11, 50)] {
+            wallet
+                .ingest_transaction(&pay(&sender, wallet.meta_address(), amount), Some(height))
+                .unwrap();
+        }
+
+        let decoys: Vec<RingMember> = (0..2 * (DEFAULT_RING_SIZE - 1))
+            .map(|_| owned_output(&sender, 5))
+            .map(|output| RingMember::from(&output))
+            .collect();
+        let chain: HashSet<RingMember> = wallet
+            .outputs()
+            .iter()
+            .map(RingMember::from)
+            .chain(decoys.iter().copied())
+            .collect();
+
+        let proof = wallet.prove_reserves(100, &decoys, b"exchange").unwrap();
+        assert!(proof.verify(&chain, &HashSet::new(), b"exchange").is_ok());
+        assert!(wallet.prove_reserves(121, &decoys, b"exchange").is_err());
+
+        let spent: HashSet<[u8; 32]> = [wallet.outputs()[0].key_image].into();
+        assert!(proof.verify(&chain, &spent, b"exchange").is_err());
+
+        let view_only =
+            Wallet::view_only(ViewingKey::import(&wallet.viewing_key().export()).unwrap());
+        assert!(matches!(
+            view_only.prove_reserves(0, &decoys, b""),
+            Err(CryptoError::ViewOnly)
+        ));
+    }
+}
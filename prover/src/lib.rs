@@ -1,30 +1,52 @@
 pub mod network;
 
 use anyhow::{Ok, Result};
-use cryptography_types::{proof::ProofData, proof::PublicInputs, transaction::PrivateTransaction};
-use sp1_sdk::{
-    include_elf, HashableKey, ProverClient, SP1ProofWithPublicValues, SP1Stdin, SP1VerifyingKey,
+use cryptography_crypto::{commit, compute_key_image, StealthKeys, TransferBuilder};
+use cryptography_types::{
+    proof::{ProofData, PublicValues},
+    transaction::{DepositTransaction, PrivateTransaction, TransactionKind},
+    wallet::OwnedOutput,
 };
+use curve25519_dalek::{constants::RISTRETTO_BASEPOINT_POINT, scalar::Scalar};
+use sp1_sdk::{include_elf, ProverClient, SP1ProofWithPublicValues, SP1Stdin};
 
 pub const ELF: &[u8] = include_elf!("cryptography-zkvm");
 
 pub fn generate_proof(tx: &PrivateTransaction) -> Result<ProofData> {
-    let client = ProverClient::from_env();
+    tx.validate()?;
+
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&TransactionKind::Transfer);
+    stdin.write(&tx.public);
+    stdin.write(&tx.witness);
+
+    prove(&stdin)
+}
+
+pub fn generate_deposit_proof(deposit: &DepositTransaction) -> Result<ProofData> {
+    deposit.validate()?;
 
     let mut stdin = SP1Stdin::new();
-    stdin.write(tx);
+    stdin.write(&TransactionKind::Deposit);
+    stdin.write(deposit);
+
+    prove(&stdin)
+}
+
+fn prove(stdin: &SP1Stdin) -> Result<ProofData> {
+    let client = ProverClient::from_env();
 
     let (pk, _vk) = client.setup(ELF);
 
-    let mut proof = client.prove(&pk, &stdin).run()?;
+    let mut proof = client.prove(&pk, stdin).run()?;
 
-    let public_inputs: PublicInputs = proof.public_values.read();
+    let public_values: PublicValues = proof.public_values.read();
 
     let proof_bytes = bincode::serialize(&proof)?;
 
     Ok(ProofData {
         proof: proof_bytes,
-        public_inputs,
+        public_values,
     })
 }
 
@@ -47,51 +69,85 @@ pub fn get_verifying_key() -> Result<Vec<u8>> {
     Ok(bincode::serialize(&vk)?)
 }
 
+/// A transfer of `amount` from a fixed sender to a fixed recipient, with real
+/// keys, commitments and ring signature. Used by the binaries and tests.
+pub fn example_transaction(amount: u64) -> PrivateTransaction {
+    let output1 = (amount * 6) / 10;
+    let output2 = amount - output1;
+
+    let g = RISTRETTO_BASEPOINT_POINT;
+
+    let sender = StealthKeys::from_seed(&[1u8; 64], 0).expect("Failed to derive sender keys");
+    let recipient = StealthKeys::from_seed(&[2u8; 64], 0).expect("Failed to derive recipient keys");
+
+    // A previously received output the sender can spend
+    let input_blinding = Scalar::from(12345u64);
+    let ring_key_offset = Scalar::from(424242u64);
+    let ring_pubkey = (sender.ring_secret.expose() + ring_key_offset) * g;
+    let input = OwnedOutput {
+        commitment: commit(amount, &input_blinding).to_bytes(),
+        amount,
+        blinding: input_blinding.to_bytes(),
+        stealth_address: [0x42u8; 20],
+        ring_pubkey: ring_pubkey.compress().to_bytes(),
+        ring_key_offset: ring_key_offset.to_bytes(),
+        key_image: compute_key_image(
+            &(sender.ring_secret.expose() + ring_key_offset),
+            &ring_pubkey,
+        )
+        .compress()
+        .to_bytes(),
+        height: Some(1),
+        spent: false,
+        spent_height: None,
+    };
+
+    let decoys: Vec<[u8; 32]> = (1..=4u64)
+        .map(|i| (Scalar::from(i * 1111) * g).compress().to_bytes())
+        .collect();
+
+    TransferBuilder::new(&sender.ring_secret)
+        .add_input(&input)
+        .add_recipient(recipient.meta_address(), output1)
+        .add_recipient(sender.meta_address(), output2)
+        .with_decoys(decoys)
+        .with_ring_size(5)
+        .build()
+        .expect("Failed to build transaction")
+}
+
 #[cfg(test)]
 mod tests {
-    use std::vec;
-
     use super::*;
-    use cryptography_types::{
-        commitment::CommitmentData, signature::RingSignatureData, stealth::StealthAddressData,
-    };
-
-    fn create_test_transaction() -> PrivateTransaction {
-        PrivateTransaction {
-            input_commitments: vec![CommitmentData::new([1u8; 32])],
-            output_commitments: vec![
-                CommitmentData::new([2u8; 32]),
-                CommitmentData::new([3u8; 32]),
-            ],
-            key_image: [4u8; 32],
-            ring: vec![[5u8; 32], [6u8; 32], [7u8; 32]],
-            stealth_addresses: vec![StealthAddressData::new(vec![8u8; 32], [0x42u8; 20])],
-            input_amounts: vec![100],
-            input_blindings: vec![[9u8; 32]],
-            output_amounts: vec![60, 40],
-            output_blindings: vec![[10u8; 32], [11u8; 32]],
-            ring_signature: RingSignatureData::new(
-                vec![[12u8; 32], [13u8; 32], [14u8; 32]],
-                vec![[15u8; 32], [16u8; 32], [17u8; 32]],
-            ),
-            secret_index: 1,
-        }
-    }
+    use cryptography_types::errors::ValidationError;
 
     #[test]
     fn test_transaction_creation() {
-        let tx = create_test_transaction();
-        assert_eq!(tx.input_amounts.len(), 1);
-        assert_eq!(tx.output_amounts.len(), 2);
+        let tx = example_transaction(100);
+        assert_eq!(tx.witness.input_amounts, vec![100]);
+        assert_eq!(tx.witness.output_amounts, vec![60, 40]);
+        assert!(tx.validate().is_ok());
+    }
 
-        let input_sum: u64 = tx.input_amounts.iter().sum();
-        let output_sum: u64 = tx.output_amounts.iter().sum();
-        assert_eq!(input_sum, output_sum);
+    #[test]
+    fn test_generate_proof_rejects_invalid_transaction() {
+        let mut tx = example_transaction(100);
+        tx.public.output_notes.pop();
+
+        let err = generate_proof(&tx).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ValidationError>(),
+            Some(&ValidationError::LengthMismatch {
+                field: "output notes",
+                expected: 2,
+                actual: 1
+            })
+        );
     }
 
     #[test]
     fn test_generate_and_verify_proof() {
-        let tx = create_test_transaction();
+        let tx = example_transaction(100);
 
         let proof_data = generate_proof(&tx).expect("Failed to generate proof");
         verify_proof(&proof_data).expect("Failed to verify proof")
@@ -0,0 +1,106 @@
+// Versioned binary wire format: MAGIC || version || SCALE(value).
+
+use crate::errors::WireError;
+use alloc::vec::Vec;
+use parity_scale_codec::{Decode, DecodeAll, Encode};
+
+pub const WIRE_MAGIC: [u8; 4] = *b"GELP";
+
+/// Current wire format version. Decoding rejects anything else.
+pub const WIRE_VERSION: u8 = 1;
+
+pub trait WireFormat: Encode + Decode {
+    fn to_wire(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(WIRE_MAGIC.len() + 1 + self.size_hint());
+        out.extend_from_slice(&WIRE_MAGIC);
+        out.push(WIRE_VERSION);
+        self.encode_to(&mut out);
+        out
+    }
+
+    fn from_wire(bytes: &[u8]) -> Result<Self, WireError> {
+        let payload = bytes.strip_prefix(&WIRE_MAGIC).ok_or(WireError::BadMagic)?;
+        let (&version, mut payload) = payload.split_first().ok_or(WireError::Decode)?;
+
+        if version != WIRE_VERSION {
+            return Err(WireError::UnsupportedVersion(version));
+        }
+
+        Self::decode_all(&mut payload).map_err(|_| WireError::Decode)
+    }
+}
+
+impl<T: Encode + Decode> WireFormat for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commitment::CommitmentData;
+    use crate::proof::{DepositPublicInputs, PublicValues};
+    use crate::stealth::StealthAddressData;
+    use crate::transaction::WithdrawalOutput;
+    use alloc::vec;
+    use scale_info::TypeInfo;
+
+    #[test]
+    fn test_wire_roundtrip() {
+        let values = PublicValues::Deposit(DepositPublicInputs {
+            token: [0x77u8; 20],
+            amount: 500,
+            output_commitment: [1u8; 32],
+            stealth_address: StealthAddressData::new(vec![2u8; 33], [0x42u8; 20]),
+        });
+
+        let bytes = values.to_wire();
+        assert_eq!(&bytes[..4], &WIRE_MAGIC);
+        assert_eq!(bytes[4], WIRE_VERSION);
+
+        match PublicValues::from_wire(&bytes).unwrap() {
+            PublicValues::Deposit(inputs) => {
+                assert_eq!(inputs.amount, 500);
+                assert_eq!(inputs.stealth_address.stealth_address, [0x42u8; 20]);
+            }
+            PublicValues::Transfer(_) => panic!("Expected deposit"),
+        }
+
+        assert_eq!(PublicValues::type_info().path.ident(), Some("PublicValues"));
+    }
+
+    #[test]
+    fn test_wire_rejects_bad_input() {
+        let withdrawal = WithdrawalOutput {
+            recipient: [0x99u8; 20],
+            amount: 42,
+        };
+        let bytes = withdrawal.to_wire();
+        assert_eq!(WithdrawalOutput::from_wire(&bytes), Ok(withdrawal));
+
+        let mut future = bytes.clone();
+        future[4] = WIRE_VERSION + 1;
+        assert_eq!(
+            WithdrawalOutput::from_wire(&future),
+            Err(WireError::UnsupportedVersion(WIRE_VERSION + 1))
+        );
+
+        assert_eq!(
+            WithdrawalOutput::from_wire(&bytes[1..]),
+            Err(WireError::BadMagic)
+        );
+        assert_eq!(
+            WithdrawalOutput::from_wire(&bytes[..bytes.len() - 1]),
+            Err(WireError::Decode)
+        );
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(
+            WithdrawalOutput::from_wire(&trailing),
+            Err(WireError::Decode)
+        );
+
+        assert_eq!(
+            CommitmentData::from_wire(&WIRE_MAGIC),
+            Err(WireError::Decode)
+        );
+    }
+}
@@ -1,9 +1,10 @@
-#![cfg_attr(not(feature = "std"), no_std)]
-
+use parity_scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Encode, Decode, TypeInfo)]
 pub struct CommitmentData {
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex"))]
     pub commitment: [u8; 32],
 }
 
@@ -0,0 +1,12 @@
+// Hash domain separators that the host crates and the zkVM guest must agree
+// on. Changing any of them changes every commitment, key image or ring
+// signature derived with it, so old ones stop verifying.
+
+/// Derives the Pedersen generator `H` from the Ristretto basepoint.
+pub const PEDERSEN_H_DOMAIN: &[u8] = b"Pedersen_H_GENERATOR_V2";
+
+/// Maps a ring member to the point its key image is taken over.
+pub const HASH_TO_POINT_DOMAIN: &[u8] = b"HASH_TO_POINTS_V1";
+
+/// Ring signature challenges.
+pub const RING_SIG_DOMAIN: &[u8] = b"RING_SIG_V1";
@@ -0,0 +1,185 @@
+use core::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BalanceError {
+    /// A sum of amounts does not fit in a `u64`.
+    AmountOverflow,
+
+    OutputAmountTooLarge {
+        index: usize,
+        amount: u64,
+        max: u64,
+    },
+
+    Unbalanced {
+        inputs: u64,
+        outputs: u64,
+    },
+}
+
+impl fmt::Display for BalanceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BalanceError::AmountOverflow => write!(f, "Amount sum overflows u64"),
+            BalanceError::OutputAmountTooLarge { index, amount, max } => {
+                write!(
+                    f,
+                    "Output {} amount {} exceeds maximum {}",
+                    index, amount, max
+                )
+            }
+            BalanceError::Unbalanced { inputs, outputs } => write!(
+                f,
+                "Transaction not balanced: inputs={}, outputs={}",
+                inputs, outputs
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BalanceError {}
+
+/// Reasons a transaction is rejected before it reaches the prover.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationError {
+    NoInputs,
+
+    TooManyInputs {
+        count: usize,
+        max: usize,
+    },
+
+    LengthMismatch {
+        field: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+
+    EmptyRing {
+        input: usize,
+    },
+
+    SignatureSizeMismatch {
+        input: usize,
+        ring_size: usize,
+        c: usize,
+        r: usize,
+    },
+
+    SecretIndexOutOfRange {
+        input: usize,
+        index: usize,
+        ring_size: usize,
+    },
+
+    DuplicateKeyImage {
+        input: usize,
+    },
+
+    KeyImageMismatch {
+        input: usize,
+    },
+
+    InvalidPoint {
+        field: &'static str,
+        index: usize,
+    },
+
+    NonCanonicalScalar {
+        field: &'static str,
+        index: usize,
+    },
+
+    Balance(BalanceError),
+}
+
+impl From<BalanceError> for ValidationError {
+    fn from(err: BalanceError) -> Self {
+        ValidationError::Balance(err)
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::NoInputs => write!(f, "Transaction has no inputs"),
+            ValidationError::TooManyInputs { count, max } => {
+                write!(
+                    f,
+                    "Transaction has {} inputs, at most {} allowed",
+                    count, max
+                )
+            }
+            ValidationError::LengthMismatch {
+                field,
+                expected,
+                actual,
+            } => write!(f, "Expected {} {}, got {}", expected, field, actual),
+            ValidationError::EmptyRing { input } => write!(f, "Ring of input {} is empty", input),
+            ValidationError::SignatureSizeMismatch {
+                input,
+                ring_size,
+                c,
+                r,
+            } => write!(
+                f,
+                "Ring signature of input {} has {} challenges and {} responses for a ring of {}",
+                input, c, r, ring_size
+            ),
+            ValidationError::SecretIndexOutOfRange {
+                input,
+                index,
+                ring_size,
+            } => write!(
+                f,
+                "Secret index {} of input {} is outside its ring of {}",
+                index, input, ring_size
+            ),
+            ValidationError::DuplicateKeyImage { input } => {
+                write!(f, "Key image of input {} is used twice", input)
+            }
+            ValidationError::KeyImageMismatch { input } => write!(
+                f,
+                "Ring signature of input {} carries a different key image",
+                input
+            ),
+            ValidationError::InvalidPoint { field, index } => {
+                write!(f, "{} {} is not a valid Ristretto point", field, index)
+            }
+            ValidationError::NonCanonicalScalar { field, index } => {
+                write!(f, "{} {} is not a canonical scalar", field, index)
+            }
+            ValidationError::Balance(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ValidationError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WireError {
+    /// Input does not start with the wire magic.
+    BadMagic,
+
+    UnsupportedVersion(u8),
+
+    /// SCALE decoding failed, including trailing bytes after the value.
+    Decode,
+}
+
+impl fmt::Display for WireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WireError::BadMagic => write!(f, "Missing wire format magic"),
+            WireError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported wire format version {}", version)
+            }
+            WireError::Decode => write!(f, "Malformed wire format payload"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for WireError {}
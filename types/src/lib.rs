@@ -1,7 +1,13 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
+
+pub mod codec;
 pub mod commitment;
+pub mod domain;
+pub mod errors;
 pub mod proof;
+pub mod serde_hex;
 pub mod signature;
 pub mod stealth;
 pub mod transaction;
@@ -1,34 +1,92 @@
+use crate::stealth::StealthAddressData;
+use crate::transaction::{EthAddress, WithdrawalOutput};
+use alloc::vec::Vec;
+use parity_scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Encode, Decode, TypeInfo)]
 pub struct ProofData {
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex::bytes"))]
     pub proof: Vec<u8>,
-    pub public_inputs: PublicInputs,
+    pub public_values: PublicValues,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Everything the guest commits. The variant tag lets the contract tell a
+/// transfer proof from a deposit proof.
+#[derive(Debug, Clone, Serialize, Deserialize, Encode, Decode, TypeInfo)]
+pub enum PublicValues {
+    Transfer(PublicInputs),
+    Deposit(DepositPublicInputs),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Encode, Decode, TypeInfo)]
 pub struct PublicInputs {
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex"))]
+    pub txid: [u8; 32],
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex::vec"))]
     pub input_commitments: Vec<[u8; 32]>,
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex::vec"))]
     pub output_commitments: Vec<[u8; 32]>,
-    pub key_image: [u8; 32],
-    pub ring: Vec<[u8; 32]>,
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex::vec"))]
+    pub key_images: Vec<[u8; 32]>,
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex::nested"))]
+    pub rings: Vec<Vec<[u8; 32]>>,
+    pub withdrawals: Vec<WithdrawalOutput>,
+    pub fee: u64,
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex::option"))]
+    pub relayer: Option<EthAddress>,
+}
+
+/// The contract checks `token`/`amount` against the funds it received and
+/// emits the stealth announcement for `stealth_address`.
+#[derive(Debug, Clone, Serialize, Deserialize, Encode, Decode, TypeInfo)]
+pub struct DepositPublicInputs {
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex"))]
+    pub token: EthAddress,
+    pub amount: u64,
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex"))]
+    pub output_commitment: [u8; 32],
+    pub stealth_address: StealthAddressData,
 }
 
 #[cfg(test)]
 mod tests {
-    use std::vec;
-
     use super::*;
+    use alloc::vec;
 
     #[test]
     fn test_public_inputs() {
         let inputs = PublicInputs {
+            txid: [0u8; 32],
             input_commitments: vec![[1u8; 32]],
             output_commitments: vec![[2u8; 32]],
-            key_image: [3u8; 32],
-            ring: vec![[4u8; 32], [5u8; 32]],
+            key_images: vec![[3u8; 32]],
+            rings: vec![vec![[4u8; 32], [5u8; 32]]],
+            withdrawals: vec![WithdrawalOutput {
+                recipient: [6u8; 20],
+                amount: 7,
+            }],
+            fee: 3,
+            relayer: Some([8u8; 20]),
         };
 
-        assert_eq!(inputs.ring.len(), 2)
+        assert_eq!(inputs.rings[0].len(), 2);
+        assert_eq!(inputs.withdrawals[0].amount, 7)
+    }
+
+    #[test]
+    fn test_public_values_variants() {
+        let deposit = PublicValues::Deposit(DepositPublicInputs {
+            token: [0u8; 20],
+            amount: 500,
+            output_commitment: [1u8; 32],
+            stealth_address: StealthAddressData::new(vec![2u8; 33], [0x42u8; 20]),
+        });
+
+        match deposit {
+            PublicValues::Deposit(inputs) => assert_eq!(inputs.amount, 500),
+            PublicValues::Transfer(_) => panic!("Expected deposit"),
+        }
     }
 }
@@ -0,0 +1,354 @@
+// `0x`-prefixed hex for byte fields in human-readable formats such as JSON.
+// Binary formats (bincode, the SP1 stdin) keep serde's compact array encoding,
+// so switching the `hex` feature on does not change any binary layout.
+//
+// Use through `#[serde(with = "crate::serde_hex")]` for `[u8; N]` and the
+// `vec`, `nested`, `option` and `bytes` submodules for the containers.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use serde::de::{self, SeqAccess, Visitor};
+use serde::ser::SerializeTuple;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+pub fn encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(2 + bytes.len() * 2);
+    out.push_str("0x");
+    for byte in bytes {
+        out.push(HEX_DIGITS[(byte >> 4) as usize] as char);
+        out.push(HEX_DIGITS[(byte & 0x0f) as usize] as char);
+    }
+    out
+}
+
+/// Decodes a `0x`-prefixed hex string. The prefix is required.
+pub fn decode(s: &str) -> Result<Vec<u8>, HexError> {
+    let digits = s.strip_prefix("0x").ok_or(HexError::MissingPrefix)?;
+    if digits.len() % 2 != 0 {
+        return Err(HexError::OddLength);
+    }
+
+    digits
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| Ok(nibble(pair[0])? << 4 | nibble(pair[1])?))
+        .collect()
+}
+
+pub fn decode_array<const N: usize>(s: &str) -> Result<[u8; N], HexError> {
+    let bytes = decode(s)?;
+    bytes.try_into().map_err(|bytes: Vec<u8>| HexError::Length {
+        expected: N,
+        actual: bytes.len(),
+    })
+}
+
+fn nibble(c: u8) -> Result<u8, HexError> {
+    match c {
+        b'0'..=b'9' => Ok(c - b'0'),
+        b'a'..=b'f' => Ok(c - b'a' + 10),
+        b'A'..=b'F' => Ok(c - b'A' + 10),
+        _ => Err(HexError::InvalidDigit(c as char)),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HexError {
+    MissingPrefix,
+    OddLength,
+    InvalidDigit(char),
+    Length { expected: usize, actual: usize },
+}
+
+impl fmt::Display for HexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingPrefix => write!(f, "hex string must start with 0x"),
+            Self::OddLength => write!(f, "hex string has an odd number of digits"),
+            Self::InvalidDigit(c) => write!(f, "invalid hex digit {:?}", c),
+            Self::Length { expected, actual } => {
+                write!(f, "expected {} bytes, got {}", expected, actual)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for HexError {}
+
+pub fn serialize<S: Serializer, const N: usize>(
+    bytes: &[u8; N],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    if serializer.is_human_readable() {
+        serializer.serialize_str(&encode(bytes))
+    } else {
+        // Same shape serde uses for `[u8; N]`.
+        let mut tuple = serializer.serialize_tuple(N)?;
+        for byte in bytes {
+            tuple.serialize_element(byte)?;
+        }
+        tuple.end()
+    }
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(
+    deserializer: D,
+) -> Result<[u8; N], D::Error> {
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_str(HexVisitor::<N>)
+    } else {
+        deserializer.deserialize_tuple(N, ArrayVisitor::<N>)
+    }
+}
+
+struct HexVisitor<const N: usize>;
+
+impl<'de, const N: usize> Visitor<'de> for HexVisitor<N> {
+    type Value = [u8; N];
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a 0x-prefixed hex string of {} bytes", N)
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+        decode_array(s).map_err(E::custom)
+    }
+}
+
+struct ArrayVisitor<const N: usize>;
+
+impl<'de, const N: usize> Visitor<'de> for ArrayVisitor<N> {
+    type Value = [u8; N];
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "an array of {} bytes", N)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut out = [0u8; N];
+        for (i, byte) in out.iter_mut().enumerate() {
+            *byte = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+        }
+        Ok(out)
+    }
+}
+
+/// A fixed-size byte array carrying the hex representation, for use inside
+/// containers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hex<const N: usize>(pub [u8; N]);
+
+impl<const N: usize> Serialize for Hex<N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize(&self.0, serializer)
+    }
+}
+
+impl<'de, const N: usize> Deserialize<'de> for Hex<N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize(deserializer).map(Hex)
+    }
+}
+
+struct HexSlice<'a, const N: usize>(&'a [[u8; N]]);
+
+impl<const N: usize> Serialize for HexSlice<'_, N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter().map(|bytes| Hex(*bytes)))
+    }
+}
+
+/// `Vec<[u8; N]>`
+pub mod vec {
+    use super::*;
+
+    pub fn serialize<S: Serializer, const N: usize>(
+        items: &[[u8; N]],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        HexSlice(items).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(
+        deserializer: D,
+    ) -> Result<Vec<[u8; N]>, D::Error> {
+        let items = Vec::<Hex<N>>::deserialize(deserializer)?;
+        Ok(items.into_iter().map(|item| item.0).collect())
+    }
+}
+
+/// `Vec<Vec<[u8; N]>>`, e.g. one ring per input.
+pub mod nested {
+    use super::*;
+
+    pub fn serialize<S: Serializer, const N: usize>(
+        items: &[Vec<[u8; N]>],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(items.iter().map(|inner| HexSlice(inner)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(
+        deserializer: D,
+    ) -> Result<Vec<Vec<[u8; N]>>, D::Error> {
+        let items = Vec::<Vec<Hex<N>>>::deserialize(deserializer)?;
+        Ok(items
+            .into_iter()
+            .map(|inner| inner.into_iter().map(|item| item.0).collect())
+            .collect())
+    }
+}
+
+/// `Option<[u8; N]>`
+pub mod option {
+    use super::*;
+
+    pub fn serialize<S: Serializer, const N: usize>(
+        value: &Option<[u8; N]>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        value.map(Hex).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(
+        deserializer: D,
+    ) -> Result<Option<[u8; N]>, D::Error> {
+        Ok(Option::<Hex<N>>::deserialize(deserializer)?.map(|item| item.0))
+    }
+}
+
+/// Variable-length `Vec<u8>`, such as a compressed secp256k1 key or a proof.
+pub mod bytes {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&encode(bytes))
+        } else {
+            bytes.serialize(serializer)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(BytesVisitor)
+        } else {
+            Vec::<u8>::deserialize(deserializer)
+        }
+    }
+
+    struct BytesVisitor;
+
+    impl<'de> Visitor<'de> for BytesVisitor {
+        type Value = Vec<u8>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "a 0x-prefixed hex string")
+        }
+
+        fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+            decode(s).map_err(E::custom)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Sample {
+        #[serde(with = "crate::serde_hex")]
+        id: [u8; 4],
+        #[serde(with = "crate::serde_hex::vec")]
+        list: Vec<[u8; 2]>,
+        #[serde(with = "crate::serde_hex::nested")]
+        rings: Vec<Vec<[u8; 2]>>,
+        #[serde(with = "crate::serde_hex::option")]
+        relayer: Option<[u8; 3]>,
+        #[serde(with = "crate::serde_hex::bytes")]
+        data: Vec<u8>,
+    }
+
+    #[derive(Serialize)]
+    struct Plain {
+        id: [u8; 4],
+        list: Vec<[u8; 2]>,
+        rings: Vec<Vec<[u8; 2]>>,
+        relayer: Option<[u8; 3]>,
+        data: Vec<u8>,
+    }
+
+    fn sample() -> Sample {
+        Sample {
+            id: [0xde, 0xad, 0xbe, 0xef],
+            list: vec![[0, 1], [0xff, 0x10]],
+            rings: vec![vec![[1, 2]], vec![]],
+            relayer: Some([0xaa; 3]),
+            data: vec![2, 3],
+        }
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        assert_eq!(encode(&[0x00, 0xab, 0x0f]), "0x00ab0f");
+        assert_eq!(decode("0x00AB0f").unwrap(), vec![0x00, 0xab, 0x0f]);
+        assert_eq!(decode("0x").unwrap(), Vec::<u8>::new());
+        assert_eq!(decode("00ab"), Err(HexError::MissingPrefix));
+        assert_eq!(decode("0xabc"), Err(HexError::OddLength));
+        assert_eq!(decode("0xzz"), Err(HexError::InvalidDigit('z')));
+        assert_eq!(
+            decode_array::<4>("0xabcd"),
+            Err(HexError::Length {
+                expected: 4,
+                actual: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_json_roundtrip() {
+        let value = sample();
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(
+            json,
+            r#"{"id":"0xdeadbeef","list":["0x0001","0xff10"],"rings":[["0x0102"],[]],"relayer":"0xaaaaaa","data":"0x0203"}"#
+        );
+        assert_eq!(serde_json::from_str::<Sample>(&json).unwrap(), value);
+
+        let short = json.replace("0xdeadbeef", "0xdead");
+        assert!(serde_json::from_str::<Sample>(&short).is_err());
+        let unprefixed = json.replace("0xdeadbeef", "deadbeef");
+        assert!(serde_json::from_str::<Sample>(&unprefixed).is_err());
+    }
+
+    #[test]
+    fn test_bincode_layout_unchanged() {
+        let value = sample();
+        let plain = Plain {
+            id: value.id,
+            list: value.list.clone(),
+            rings: value.rings.clone(),
+            relayer: value.relayer,
+            data: value.data.clone(),
+        };
+
+        let bytes = bincode::serialize(&value).unwrap();
+        assert_eq!(bytes, bincode::serialize(&plain).unwrap());
+        assert_eq!(bincode::deserialize::<Sample>(&bytes).unwrap(), value);
+
+        let none = Sample {
+            relayer: None,
+            ..sample()
+        };
+        let bytes = bincode::serialize(&none).unwrap();
+        assert_eq!(bincode::deserialize::<Sample>(&bytes).unwrap(), none);
+    }
+}
@@ -1,14 +1,23 @@
+use alloc::vec::Vec;
+use parity_scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Wire form of an LSAG signature: compressed key image and canonical
+/// challenge/response scalars, one pair per ring member.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode, TypeInfo)]
 pub struct RingSignatureData {
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex"))]
+    pub key_image: [u8; 32],
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex::vec"))]
     pub c: Vec<[u8; 32]>,
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex::vec"))]
     pub r: Vec<[u8; 32]>,
 }
 
 impl RingSignatureData {
-    pub fn new(c: Vec<[u8; 32]>, r: Vec<[u8; 32]>) -> Self {
-        Self { c, r }
+    pub fn new(key_image: [u8; 32], c: Vec<[u8; 32]>, r: Vec<[u8; 32]>) -> Self {
+        Self { key_image, c, r }
     }
 
     pub fn ring_size(&self) -> usize {
@@ -19,14 +28,17 @@ impl RingSignatureData {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloc::vec;
 
     #[test]
     fn test_ring_signature_data() {
         let sig = RingSignatureData::new(
+            [9u8; 32],
             vec![[1u8; 32], [2u8; 32], [3u8; 32]],
             vec![[4u8; 32], [5u8; 32], [6u8; 32]],
         );
 
+        assert_eq!(sig.key_image, [9u8; 32]);
         assert_eq!(sig.ring_size(), 3);
         assert_eq!(sig.c.len(), 3);
         assert_eq!(sig.r.len(), 3);
@@ -1,10 +1,15 @@
+use alloc::vec::Vec;
+use parity_scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
 use serde::{Deserialize, Serialize};
 
 pub type EthAddress = [u8; 20];
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Encode, Decode, TypeInfo)]
 pub struct StealthAddressData {
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex::bytes"))]
     pub ephemeral_pubkey: Vec<u8>,
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex"))]
     pub stealth_address: EthAddress,
 }
 
@@ -17,9 +22,22 @@ impl StealthAddressData {
     }
 }
 
+/// Per-output data the recipient needs to spend it: the one-time ring key
+/// and the commitment opening, encrypted under the stealth shared secret.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct OutputNote {
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex"))]
+    pub ring_pubkey: [u8; 32],
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex"))]
+    pub encrypted_amount: [u8; 8],
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex"))]
+    pub encrypted_blinding: [u8; 32],
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloc::vec;
 
     #[test]
     fn test_stealth_address_data() {
@@ -1,49 +1,477 @@
 use crate::commitment::CommitmentData;
+use crate::errors::{BalanceError, ValidationError};
 use crate::signature::RingSignatureData;
-use crate::stealth::StealthAddressData;
+use crate::stealth::{OutputNote, StealthAddressData};
+use alloc::vec::Vec;
+use core::{fmt, mem};
+use curve25519_dalek::{ristretto::CompressedRistretto, scalar::Scalar};
+use parity_scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
 use serde::{Deserialize, Serialize};
+use tiny_keccak::{Hasher, Keccak};
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 pub type EthAddress = [u8; 20];
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Token address used for native ETH deposits.
+pub const NATIVE_TOKEN: EthAddress = [0u8; 20];
+
+/// Prefix of the canonical transaction encoding.
+pub const TX_ENCODING_MAGIC: &[u8; 8] = b"GELAP_TX";
+
+/// Version byte following [`TX_ENCODING_MAGIC`]. Bump on any layout change.
+pub const TX_ENCODING_VERSION: u8 = 1;
+
+/// Most inputs a transfer may have. Each input costs the guest one ring
+/// signature verification, and this many still fit its cycle budget at the
+/// default ring size.
+pub const MAX_INPUTS: usize = 16;
+
+/// Sums amounts without wrapping. Returns `None` if the total exceeds `u64::MAX`.
+pub fn checked_sum<I: IntoIterator<Item = u64>>(amounts: I) -> Option<u64> {
+    amounts
+        .into_iter()
+        .try_fold(0u64, |acc, amount| acc.checked_add(amount))
+}
+
+/// Tells the guest which transaction follows on its input stream.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub enum TransactionKind {
+    Transfer,
+    Deposit,
+}
+
+/// A transfer as handed to the prover. Only `public` may be logged or sent to
+/// relayers; `witness` never leaves the wallet and the prover.
+#[derive(Debug, Serialize, Deserialize, Encode, Decode, TypeInfo)]
 pub struct PrivateTransaction {
+    pub public: PublicTransaction,
+    pub witness: TransactionWitness,
+}
+
+impl PrivateTransaction {
+    pub fn new(public: PublicTransaction, witness: TransactionWitness) -> Self {
+        Self { public, witness }
+    }
+
+    pub fn into_parts(self) -> (PublicTransaction, TransactionWitness) {
+        (self.public, self.witness)
+    }
+
+    /// Checks everything the guest would otherwise panic on, except the ring
+    /// signatures and commitment openings themselves.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        self.validate_structure()?;
+
+        let public = &self.public;
+        let witness = &self.witness;
+        for (ring, signature) in public.rings.iter().zip(&public.ring_signatures) {
+            check_points("ring member", ring)?;
+            check_scalars("challenge", &signature.c)?;
+            check_scalars("response", &signature.r)?;
+        }
+        check_points("key image", &public.key_images)?;
+        check_points(
+            "input commitment",
+            public.input_commitments.iter().map(|c| &c.commitment),
+        )?;
+        check_points(
+            "output commitment",
+            public.output_commitments.iter().map(|c| &c.commitment),
+        )?;
+        check_points(
+            "output note ring key",
+            public.output_notes.iter().map(|n| &n.ring_pubkey),
+        )?;
+        check_scalars("input blinding", &witness.input_blindings)?;
+        check_scalars("output blinding", &witness.output_blindings)
+    }
+
+    /// [`Self::validate`] without decoding any point or scalar: lengths, ring
+    /// shapes, key image uniqueness and the balance. The guest runs this and
+    /// decodes each point and scalar once as it uses it.
+    pub fn validate_structure(&self) -> Result<(), ValidationError> {
+        let public = &self.public;
+        let witness = &self.witness;
+
+        let n_inputs = public.input_commitments.len();
+        if n_inputs == 0 {
+            return Err(ValidationError::NoInputs);
+        }
+        if n_inputs > MAX_INPUTS {
+            return Err(ValidationError::TooManyInputs {
+                count: n_inputs,
+                max: MAX_INPUTS,
+            });
+        }
+        check_len("input amounts", n_inputs, witness.input_amounts.len())?;
+        check_len("input blindings", n_inputs, witness.input_blindings.len())?;
+        check_len("key images", n_inputs, public.key_images.len())?;
+        check_len("rings", n_inputs, public.rings.len())?;
+        check_len("ring signatures", n_inputs, public.ring_signatures.len())?;
+        check_len("secret indices", n_inputs, witness.secret_indices.len())?;
+
+        let n_outputs = public.output_commitments.len();
+        check_len("output amounts", n_outputs, witness.output_amounts.len())?;
+        check_len(
+            "output blindings",
+            n_outputs,
+            witness.output_blindings.len(),
+        )?;
+        check_len(
+            "stealth addresses",
+            n_outputs,
+            public.stealth_addresses.len(),
+        )?;
+        check_len("output notes", n_outputs, public.output_notes.len())?;
+
+        for (input, ring) in public.rings.iter().enumerate() {
+            let signature = &public.ring_signatures[input];
+            if ring.is_empty() {
+                return Err(ValidationError::EmptyRing { input });
+            }
+            if signature.c.len() != ring.len() || signature.r.len() != ring.len() {
+                return Err(ValidationError::SignatureSizeMismatch {
+                    input,
+                    ring_size: ring.len(),
+                    c: signature.c.len(),
+                    r: signature.r.len(),
+                });
+            }
+            if signature.key_image != public.key_images[input] {
+                return Err(ValidationError::KeyImageMismatch { input });
+            }
+
+            let index = witness.secret_indices[input] as usize;
+            if index >= ring.len() {
+                return Err(ValidationError::SecretIndexOutOfRange {
+                    input,
+                    index,
+                    ring_size: ring.len(),
+                });
+            }
+        }
+
+        for (input, key_image) in public.key_images.iter().enumerate() {
+            if public.key_images[..input].contains(key_image) {
+                return Err(ValidationError::DuplicateKeyImage { input });
+            }
+        }
+
+        let inputs = checked_sum(witness.input_amounts.iter().copied())
+            .ok_or(BalanceError::AmountOverflow)?;
+        let outputs = checked_sum(
+            witness
+                .output_amounts
+                .iter()
+                .copied()
+                .chain(public.withdrawals.iter().map(|w| w.amount))
+                .chain(core::iter::once(public.fee)),
+        )
+        .ok_or(BalanceError::AmountOverflow)?;
+        if inputs != outputs {
+            return Err(BalanceError::Unbalanced { inputs, outputs }.into());
+        }
+
+        Ok(())
+    }
+}
+
+fn check_len(field: &'static str, expected: usize, actual: usize) -> Result<(), ValidationError> {
+    if expected != actual {
+        return Err(ValidationError::LengthMismatch {
+            field,
+            expected,
+            actual,
+        });
+    }
+    Ok(())
+}
+
+fn check_points<'a, I: IntoIterator<Item = &'a [u8; 32]>>(
+    field: &'static str,
+    points: I,
+) -> Result<(), ValidationError> {
+    for (index, bytes) in points.into_iter().enumerate() {
+        if CompressedRistretto(*bytes).decompress().is_none() {
+            return Err(ValidationError::InvalidPoint { field, index });
+        }
+    }
+    Ok(())
+}
+
+fn check_scalars(field: &'static str, scalars: &[[u8; 32]]) -> Result<(), ValidationError> {
+    for (index, bytes) in scalars.iter().enumerate() {
+        if bool::from(Scalar::from_canonical_bytes(*bytes).is_none()) {
+            return Err(ValidationError::NonCanonicalScalar { field, index });
+        }
+    }
+    Ok(())
+}
+
+/// The on-chain part of a transfer. Each input is spent with its own ring,
+/// key image and ring signature, at the same index in each vector.
+#[derive(Debug, Clone, Serialize, Deserialize, Encode, Decode, TypeInfo)]
+pub struct PublicTransaction {
     pub input_commitments: Vec<CommitmentData>,
     pub output_commitments: Vec<CommitmentData>,
-    pub key_image: [u8; 32],
-    pub ring: Vec<[u8; 32]>,
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex::vec"))]
+    pub key_images: Vec<[u8; 32]>,
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex::nested"))]
+    pub rings: Vec<Vec<[u8; 32]>>,
     pub stealth_addresses: Vec<StealthAddressData>,
+    pub output_notes: Vec<OutputNote>,
+    pub withdrawals: Vec<WithdrawalOutput>,
+    /// Public fee paid out of the inputs, on top of outputs and withdrawals.
+    pub fee: u64,
+    /// Address the contract pays `fee` to. `None` leaves the fee in the pool.
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex::option"))]
+    pub relayer: Option<EthAddress>,
+    pub ring_signatures: Vec<RingSignatureData>,
+}
+
+impl PublicTransaction {
+    /// Canonical encoding of everything except the ring signatures, which sign
+    /// its hash. Integers are big-endian and lists are prefixed with a `u32`
+    /// length; `TransactionHash.sol` reproduces it with `abi.encodePacked`.
+    ///
+    /// ```text
+    /// "GELAP_TX" || version: u8
+    /// || u32 inputs      || (commitment || key_image || u32 ring_len || ring[])[]
+    /// || u32 outputs     || (commitment || stealth_address || u32 len || ephemeral_pubkey)[]
+    /// || u32 notes       || (ring_pubkey || encrypted_amount || encrypted_blinding)[]
+    /// || u32 withdrawals || (recipient || u64 amount)[]
+    /// || u64 fee || u8 has_relayer || relayer (zero address if none)
+    /// ```
+    ///
+    /// Key images, rings and stealth addresses are interleaved with their
+    /// commitments, so their counts must match or the encoding is ambiguous.
+    pub fn canonical_bytes(&self) -> Result<Vec<u8>, ValidationError> {
+        let n_inputs = self.input_commitments.len();
+        check_len("key images", n_inputs, self.key_images.len())?;
+        check_len("rings", n_inputs, self.rings.len())?;
+        check_len(
+            "stealth addresses",
+            self.output_commitments.len(),
+            self.stealth_addresses.len(),
+        )?;
+
+        let mut out = Vec::new();
+        out.extend_from_slice(TX_ENCODING_MAGIC);
+        out.push(TX_ENCODING_VERSION);
+
+        push_len(&mut out, n_inputs);
+        for ((commitment, key_image), ring) in self
+            .input_commitments
+            .iter()
+            .zip(&self.key_images)
+            .zip(&self.rings)
+        {
+            out.extend_from_slice(&commitment.commitment);
+            out.extend_from_slice(key_image);
+            push_len(&mut out, ring.len());
+            for member in ring {
+                out.extend_from_slice(member);
+            }
+        }
+
+        push_len(&mut out, self.output_commitments.len());
+        for (commitment, stealth) in self.output_commitments.iter().zip(&self.stealth_addresses) {
+            out.extend_from_slice(&commitment.commitment);
+            out.extend_from_slice(&stealth.stealth_address);
+            push_len(&mut out, stealth.ephemeral_pubkey.len());
+            out.extend_from_slice(&stealth.ephemeral_pubkey);
+        }
+
+        push_len(&mut out, self.output_notes.len());
+        for note in &self.output_notes {
+            out.extend_from_slice(&note.ring_pubkey);
+            out.extend_from_slice(&note.encrypted_amount);
+            out.extend_from_slice(&note.encrypted_blinding);
+        }
+
+        push_len(&mut out, self.withdrawals.len());
+        for withdrawal in &self.withdrawals {
+            out.extend_from_slice(&withdrawal.recipient);
+            out.extend_from_slice(&withdrawal.amount.to_be_bytes());
+        }
+
+        out.extend_from_slice(&self.fee.to_be_bytes());
+        match self.relayer {
+            Some(relayer) => {
+                out.push(1);
+                out.extend_from_slice(&relayer);
+            }
+            None => {
+                out.push(0);
+                out.extend_from_slice(&[0u8; 20]);
+            }
+        }
+
+        Ok(out)
+    }
+
+    /// `keccak256(canonical_bytes())`. This is the message every input's ring
+    /// signature signs.
+    pub fn txid(&self) -> Result<[u8; 32], ValidationError> {
+        let mut hasher = Keccak::v256();
+        hasher.update(&self.canonical_bytes()?);
+
+        let mut hash = [0u8; 32];
+        hasher.finalize(&mut hash);
+        Ok(hash)
+    }
+}
+
+fn push_len(out: &mut Vec<u8>, len: usize) {
+    out.extend_from_slice(&(len as u32).to_be_bytes());
+}
 
+/// Openings of the commitments and the signer's ring position. Wiped on drop;
+/// Debug output is redacted.
+#[derive(Serialize, Deserialize, Encode, Decode, TypeInfo, Zeroize, ZeroizeOnDrop)]
+pub struct TransactionWitness {
     pub input_amounts: Vec<u64>,
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex::vec"))]
     pub input_blindings: Vec<[u8; 32]>,
     pub output_amounts: Vec<u64>,
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex::vec"))]
     pub output_blindings: Vec<[u8; 32]>,
-    pub ring_signature: RingSignatureData,
-    pub secret_index: usize,
+    pub secret_indices: Vec<u32>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl fmt::Debug for TransactionWitness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TransactionWitness")
+            .field("inputs", &self.input_amounts.len())
+            .field("outputs", &self.output_amounts.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Shields a public ETH or ERC-20 deposit into a new commitment. There is no
+/// ring signature: the depositor is already public on-chain. The blinding is
+/// wiped on drop and left out of Debug output.
+#[derive(Serialize, Deserialize, Encode, Decode, TypeInfo, Zeroize, ZeroizeOnDrop)]
+pub struct DepositTransaction {
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex"))]
+    #[zeroize(skip)]
+    pub token: EthAddress,
+    #[zeroize(skip)]
+    pub amount: u64,
+    #[zeroize(skip)]
+    pub output_commitment: CommitmentData,
+    #[zeroize(skip)]
+    pub stealth_address: StealthAddressData,
+
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex"))]
+    pub output_blinding: [u8; 32],
+}
+
+impl fmt::Debug for DepositTransaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DepositTransaction")
+            .field("token", &self.token)
+            .field("amount", &self.amount)
+            .field("output_commitment", &self.output_commitment)
+            .field("stealth_address", &self.stealth_address)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DepositTransaction {
+    pub fn new(
+        token: EthAddress,
+        amount: u64,
+        output_commitment: CommitmentData,
+        stealth_address: StealthAddressData,
+        output_blinding: [u8; 32],
+    ) -> Self {
+        Self {
+            token,
+            amount,
+            output_commitment,
+            stealth_address,
+            output_blinding,
+        }
+    }
+
+    pub fn is_native(&self) -> bool {
+        self.token == NATIVE_TOKEN
+    }
+
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        check_points("deposit commitment", [&self.output_commitment.commitment])?;
+        check_scalars("deposit blinding", &[self.output_blinding])
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Encode, Decode, TypeInfo)]
 pub struct TransactionOutput {
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex"))]
     pub commitment: [u8; 32],
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex"))]
     pub stealth_address: EthAddress,
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex::bytes"))]
     pub ephemeral_pubkey: Vec<u8>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Unshields `amount` to a public Ethereum address. The amount is revealed
+/// and paid out by the pool contract.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct WithdrawalOutput {
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex"))]
+    pub recipient: EthAddress,
+    pub amount: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Encode, Decode, TypeInfo)]
 pub struct TransactionInput {
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex"))]
     pub commitment: [u8; 32],
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex"))]
     pub key_image: [u8; 32],
 }
 
-#[derive(Debug, Default)]
+/// Blindings are wiped on drop and left out of Debug output.
+#[derive(Default)]
 pub struct TransactionBuilder {
     inputs: Vec<TransactionInput>,
     outputs: Vec<TransactionOutput>,
+    output_notes: Vec<OutputNote>,
+    withdrawals: Vec<WithdrawalOutput>,
+    fee: u64,
+    relayer: Option<EthAddress>,
+    max_output_amount: Option<u64>,
     input_amounts: Vec<u64>,
     input_blindings: Vec<[u8; 32]>,
     output_amounts: Vec<u64>,
     output_blindings: Vec<[u8; 32]>,
 }
 
+impl fmt::Debug for TransactionBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TransactionBuilder")
+            .field("inputs", &self.inputs)
+            .field("outputs", &self.outputs)
+            .field("output_notes", &self.output_notes)
+            .field("withdrawals", &self.withdrawals)
+            .field("fee", &self.fee)
+            .field("relayer", &self.relayer)
+            .field("max_output_amount", &self.max_output_amount)
+            .field("input_amounts", &self.input_amounts)
+            .field("output_amounts", &self.output_amounts)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for TransactionBuilder {
+    fn drop(&mut self) {
+        self.input_blindings.zeroize();
+        self.output_blindings.zeroize();
+    }
+}
+
 impl TransactionBuilder {
     pub fn new() -> Self {
         Self::default()
@@ -84,6 +512,36 @@ impl TransactionBuilder {
         self
     }
 
+    /// Attaches the note for the most recently added output.
+    pub fn add_output_note(mut self, note: OutputNote) -> Self {
+        self.output_notes.push(note);
+        self
+    }
+
+    pub fn add_withdrawal(mut self, recipient: EthAddress, amount: u64) -> Self {
+        self.withdrawals
+            .push(WithdrawalOutput { recipient, amount });
+        self
+    }
+
+    pub fn with_fee(mut self, fee: u64) -> Self {
+        self.fee = fee;
+        self
+    }
+
+    pub fn with_relayer(mut self, relayer: EthAddress) -> Self {
+        self.relayer = Some(relayer);
+        self
+    }
+
+    /// Rejects any single output or withdrawal above `max` in `check_balance`.
+    /// Client-side policy only; [`PrivateTransaction::validate`] does not
+    /// check it.
+    pub fn with_max_output_amount(mut self, max: u64) -> Self {
+        self.max_output_amount = Some(max);
+        self
+    }
+
     pub fn inputs(&self) -> &[TransactionInput] {
         &self.inputs
     }
@@ -92,6 +550,18 @@ impl TransactionBuilder {
         &self.outputs
     }
 
+    pub fn withdrawals(&self) -> &[WithdrawalOutput] {
+        &self.withdrawals
+    }
+
+    pub fn fee(&self) -> u64 {
+        self.fee
+    }
+
+    pub fn relayer(&self) -> Option<EthAddress> {
+        self.relayer
+    }
+
     pub fn input_amounts(&self) -> &[u64] {
         &self.input_amounts
     }
@@ -100,17 +570,124 @@ impl TransactionBuilder {
         &self.output_amounts
     }
 
+    /// Checks the balance and assembles the transaction around the per-input
+    /// rings and their signatures.
+    pub fn build(
+        mut self,
+        rings: Vec<Vec<[u8; 32]>>,
+        ring_signatures: Vec<RingSignatureData>,
+        secret_indices: Vec<u32>,
+    ) -> Result<PrivateTransaction, BalanceError> {
+        self.check_balance()?;
+
+        let public = PublicTransaction {
+            input_commitments: self
+                .inputs
+                .iter()
+                .map(|i| CommitmentData::new(i.commitment))
+                .collect(),
+            output_commitments: self
+                .outputs
+                .iter()
+                .map(|o| CommitmentData::new(o.commitment))
+                .collect(),
+            key_images: self.inputs.iter().map(|i| i.key_image).collect(),
+            rings,
+            stealth_addresses: mem::take(&mut self.outputs)
+                .into_iter()
+                .map(|o| StealthAddressData::new(o.ephemeral_pubkey, o.stealth_address))
+                .collect(),
+            output_notes: mem::take(&mut self.output_notes),
+            withdrawals: mem::take(&mut self.withdrawals),
+            fee: self.fee,
+            relayer: self.relayer,
+            ring_signatures,
+        };
+
+        let witness = TransactionWitness {
+            input_amounts: mem::take(&mut self.input_amounts),
+            input_blindings: mem::take(&mut self.input_blindings),
+            output_amounts: mem::take(&mut self.output_amounts),
+            output_blindings: mem::take(&mut self.output_blindings),
+            secret_indices,
+        };
+
+        Ok(PrivateTransaction::new(public, witness))
+    }
+
     pub fn verify_balance(&self) -> bool {
-        let input_sum: u64 = self.input_amounts.iter().sum();
-        let output_sum: u64 = self.output_amounts.iter().sum();
+        self.check_balance().is_ok()
+    }
+
+    pub fn check_balance(&self) -> Result<(), BalanceError> {
+        let spent: Vec<u64> = self
+            .output_amounts
+            .iter()
+            .copied()
+            .chain(self.withdrawals.iter().map(|w| w.amount))
+            .collect();
+
+        if let Some(max) = self.max_output_amount {
+            if let Some((index, &amount)) = spent.iter().enumerate().find(|(_, a)| **a > max) {
+                return Err(BalanceError::OutputAmountTooLarge { index, amount, max });
+            }
+        }
 
-        input_sum == output_sum
+        let inputs =
+            checked_sum(self.input_amounts.iter().copied()).ok_or(BalanceError::AmountOverflow)?;
+        let outputs = checked_sum(spent.into_iter().chain(core::iter::once(self.fee)))
+            .ok_or(BalanceError::AmountOverflow)?;
+
+        if inputs != outputs {
+            return Err(BalanceError::Unbalanced { inputs, outputs });
+        }
+
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloc::{format, vec};
+    use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+
+    fn point(n: u64) -> [u8; 32] {
+        (Scalar::from(n) * RISTRETTO_BASEPOINT_POINT)
+            .compress()
+            .to_bytes()
+    }
+
+    fn scalar(n: u64) -> [u8; 32] {
+        Scalar::from(n).to_bytes()
+    }
+
+    fn note(n: u64) -> OutputNote {
+        OutputNote {
+            ring_pubkey: point(n),
+            encrypted_amount: [0u8; 8],
+            encrypted_blinding: [0u8; 32],
+        }
+    }
+
+    fn valid_transaction() -> PrivateTransaction {
+        TransactionBuilder::new()
+            .add_input(point(1), point(2), 100, scalar(3))
+            .add_output(point(4), [0x42u8; 20], vec![5u8; 33], 60, scalar(6))
+            .add_output_note(note(19))
+            .add_output(point(7), [0x43u8; 20], vec![8u8; 33], 40, scalar(9))
+            .add_output_note(note(20))
+            .build(
+                vec![vec![point(10), point(11), point(12)]],
+                vec![RingSignatureData::new(
+                    point(2),
+                    vec![scalar(13), scalar(14), scalar(15)],
+                    vec![scalar(16), scalar(17), scalar(18)],
+                )],
+                vec![1],
+            )
+            .unwrap()
+    }
 
     #[test]
     fn test_transaction_builder() {
@@ -123,4 +700,379 @@ mod tests {
         assert_eq!(builder.inputs().len(), 1);
         assert_eq!(builder.outputs().len(), 2);
     }
+
+    #[test]
+    fn test_transaction_builder_withdrawal() {
+        let builder = TransactionBuilder::new()
+            .add_input([1u8; 32], [2u8; 32], 100, [3u8; 32])
+            .add_output([4u8; 32], [0x42u8; 20], vec![5u8; 33], 70, [6u8; 32])
+            .add_withdrawal([0x99u8; 20], 30);
+
+        assert!(builder.verify_balance());
+        assert_eq!(builder.withdrawals().len(), 1);
+        assert_eq!(builder.withdrawals()[0].recipient, [0x99u8; 20]);
+
+        let unbalanced = TransactionBuilder::new()
+            .add_input([1u8; 32], [2u8; 32], 100, [3u8; 32])
+            .add_output([4u8; 32], [0x42u8; 20], vec![5u8; 33], 70, [6u8; 32])
+            .add_withdrawal([0x99u8; 20], 31);
+
+        assert!(!unbalanced.verify_balance());
+    }
+
+    #[test]
+    fn test_transaction_builder_fee() {
+        let builder = TransactionBuilder::new()
+            .add_input([1u8; 32], [2u8; 32], 100, [3u8; 32])
+            .add_output([4u8; 32], [0x42u8; 20], vec![5u8; 33], 95, [6u8; 32])
+            .with_fee(5)
+            .with_relayer([0x55u8; 20]);
+
+        assert!(builder.verify_balance());
+        assert_eq!(builder.fee(), 5);
+        assert_eq!(builder.relayer(), Some([0x55u8; 20]));
+
+        let no_fee = TransactionBuilder::new()
+            .add_input([1u8; 32], [2u8; 32], 100, [3u8; 32])
+            .add_output([4u8; 32], [0x42u8; 20], vec![5u8; 33], 95, [6u8; 32]);
+
+        assert!(!no_fee.verify_balance());
+        assert_eq!(no_fee.relayer(), None);
+    }
+
+    #[test]
+    fn test_checked_sum() {
+        assert_eq!(checked_sum([u64::MAX - 1, 1]), Some(u64::MAX));
+        assert_eq!(checked_sum([u64::MAX, 1]), None);
+        assert_eq!(checked_sum([]), Some(0));
+    }
+
+    #[test]
+    fn test_balance_rejects_wrapping_sums() {
+        // Wrapping arithmetic would make u64::MAX + 2 "equal" 1.
+        let builder = TransactionBuilder::new()
+            .add_input([1u8; 32], [2u8; 32], u64::MAX, [3u8; 32])
+            .add_input([4u8; 32], [5u8; 32], 2, [6u8; 32])
+            .add_output([7u8; 32], [0x42u8; 20], vec![8u8; 33], 1, [9u8; 32]);
+
+        assert_eq!(builder.check_balance(), Err(BalanceError::AmountOverflow));
+        assert!(!builder.verify_balance());
+
+        let builder = TransactionBuilder::new()
+            .add_input([1u8; 32], [2u8; 32], 1, [3u8; 32])
+            .add_output([4u8; 32], [0x42u8; 20], vec![5u8; 33], u64::MAX, [6u8; 32])
+            .add_withdrawal([0x99u8; 20], 2);
+
+        assert_eq!(builder.check_balance(), Err(BalanceError::AmountOverflow));
+
+        let builder = TransactionBuilder::new()
+            .add_input([1u8; 32], [2u8; 32], u64::MAX, [3u8; 32])
+            .add_output(
+                [4u8; 32],
+                [0x42u8; 20],
+                vec![5u8; 33],
+                u64::MAX - 10,
+                [6u8; 32],
+            )
+            .with_fee(10);
+
+        assert_eq!(builder.check_balance(), Ok(()));
+    }
+
+    #[test]
+    fn test_max_output_amount() {
+        let builder = TransactionBuilder::new()
+            .add_input([1u8; 32], [2u8; 32], 1000, [3u8; 32])
+            .add_output([4u8; 32], [0x42u8; 20], vec![5u8; 33], 400, [6u8; 32])
+            .add_output([7u8; 32], [0x43u8; 20], vec![8u8; 33], 600, [9u8; 32]);
+
+        assert!(builder.verify_balance());
+
+        let limited = builder.with_max_output_amount(500);
+        assert_eq!(
+            limited.check_balance(),
+            Err(BalanceError::OutputAmountTooLarge {
+                index: 1,
+                amount: 600,
+                max: 500
+            })
+        );
+    }
+
+    #[test]
+    fn test_build_splits_public_and_witness() {
+        let tx = TransactionBuilder::new()
+            .add_input([1u8; 32], [2u8; 32], 100, [3u8; 32])
+            .add_output([4u8; 32], [0x42u8; 20], vec![5u8; 33], 90, [6u8; 32])
+            .with_fee(10)
+            .build(
+                vec![vec![[7u8; 32], [8u8; 32]]],
+                vec![RingSignatureData::new(
+                    [2u8; 32],
+                    vec![[9u8; 32]; 2],
+                    vec![[10u8; 32]; 2],
+                )],
+                vec![1],
+            )
+            .unwrap();
+
+        assert_eq!(tx.public.output_commitments[0].commitment, [4u8; 32]);
+        assert_eq!(tx.public.stealth_addresses[0].stealth_address, [0x42u8; 20]);
+        assert_eq!(tx.public.key_images, vec![[2u8; 32]]);
+        assert_eq!(tx.public.fee, 10);
+        assert_eq!(tx.witness.output_amounts, vec![90]);
+        assert_eq!(tx.witness.secret_indices, vec![1]);
+
+        let debug = format!("{:?}", tx.witness);
+        assert!(!debug.contains("90"));
+
+        let unbalanced = TransactionBuilder::new()
+            .add_input([1u8; 32], [2u8; 32], 100, [3u8; 32])
+            .build(vec![], vec![], vec![]);
+        assert!(unbalanced.is_err());
+    }
+
+    #[test]
+    fn test_txid() {
+        let tx = valid_transaction();
+        let txid = tx.public.txid().unwrap();
+
+        // Ring signatures sign the txid, so they are not part of it
+        let mut resigned = tx.public.clone();
+        resigned.ring_signatures[0].c[0] = scalar(99);
+        assert_eq!(resigned.txid(), Ok(txid));
+
+        let mut higher_fee = tx.public.clone();
+        higher_fee.fee = 1;
+        assert_ne!(higher_fee.txid(), Ok(txid));
+
+        let mut relayed = tx.public.clone();
+        relayed.relayer = Some([0u8; 20]);
+        assert_ne!(relayed.txid(), Ok(txid));
+
+        let bytes = tx.public.canonical_bytes().unwrap();
+        assert_eq!(&bytes[..8], TX_ENCODING_MAGIC);
+        assert_eq!(bytes[8], TX_ENCODING_VERSION);
+
+        // Entries without a commitment would otherwise not be hashed at all
+        let mut extra = tx.public.clone();
+        extra.key_images.push(point(50));
+        assert_eq!(
+            extra.txid(),
+            Err(ValidationError::LengthMismatch {
+                field: "key images",
+                expected: 1,
+                actual: 2
+            })
+        );
+
+        let mut missing = tx.public.clone();
+        missing.stealth_addresses.pop();
+        assert!(missing.canonical_bytes().is_err());
+    }
+
+    #[test]
+    fn test_txid_vector() {
+        // Shared with TransactionHash.t.sol
+        let public = PublicTransaction {
+            input_commitments: vec![CommitmentData::new([0x11u8; 32])],
+            output_commitments: vec![CommitmentData::new([0x22u8; 32])],
+            key_images: vec![[0x33u8; 32]],
+            rings: vec![vec![[0x44u8; 32], [0x55u8; 32]]],
+            stealth_addresses: vec![StealthAddressData::new(vec![0x02u8; 33], [0x66u8; 20])],
+            output_notes: vec![OutputNote {
+                ring_pubkey: [0x77u8; 32],
+                encrypted_amount: [0x88u8; 8],
+                encrypted_blinding: [0x99u8; 32],
+            }],
+            withdrawals: vec![WithdrawalOutput {
+                recipient: [0xaau8; 20],
+                amount: 1_000,
+            }],
+            fee: 25,
+            relayer: Some([0xbbu8; 20]),
+            ring_signatures: vec![],
+        };
+
+        assert_eq!(public.canonical_bytes().unwrap().len(), 375);
+        // 0x5bda5303dcda1895593f984fbc70d1b0cae4f8a3b2c97808c766d3650a12c177
+        let expected: [u8; 32] = [
+            0x5b, 0xda, 0x53, 0x03, 0xdc, 0xda, 0x18, 0x95, 0x59, 0x3f, 0x98, 0x4f, 0xbc, 0x70,
+            0xd1, 0xb0, 0xca, 0xe4, 0xf8, 0xa3, 0xb2, 0xc9, 0x78, 0x08, 0xc7, 0x66, 0xd3, 0x65,
+            0x0a, 0x12, 0xc1, 0x77,
+        ];
+        assert_eq!(public.txid(), Ok(expected));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(valid_transaction().validate(), Ok(()));
+
+        let mut tx = valid_transaction();
+        tx.witness.input_amounts.push(1);
+        assert_eq!(
+            tx.validate(),
+            Err(ValidationError::LengthMismatch {
+                field: "input amounts",
+                expected: 1,
+                actual: 2
+            })
+        );
+
+        let mut tx = valid_transaction();
+        tx.public.ring_signatures[0].r.pop();
+        assert!(matches!(
+            tx.validate(),
+            Err(ValidationError::SignatureSizeMismatch { r: 2, .. })
+        ));
+
+        let mut tx = valid_transaction();
+        let n = MAX_INPUTS + 1;
+        tx.public.input_commitments = vec![CommitmentData::new(point(1)); n];
+        assert_eq!(
+            tx.validate(),
+            Err(ValidationError::TooManyInputs {
+                count: n,
+                max: MAX_INPUTS
+            })
+        );
+
+        let mut tx = valid_transaction();
+        tx.public.ring_signatures[0].key_image = point(3);
+        assert_eq!(
+            tx.validate(),
+            Err(ValidationError::KeyImageMismatch { input: 0 })
+        );
+
+        let mut tx = valid_transaction();
+        tx.witness.secret_indices[0] = 3;
+        assert!(matches!(
+            tx.validate(),
+            Err(ValidationError::SecretIndexOutOfRange { index: 3, .. })
+        ));
+
+        let mut tx = valid_transaction();
+        tx.public.rings[0][2] = [0xffu8; 32];
+        assert_eq!(
+            tx.validate(),
+            Err(ValidationError::InvalidPoint {
+                field: "ring member",
+                index: 2
+            })
+        );
+        // Left to the guest, which decodes every point as it uses it
+        assert_eq!(tx.validate_structure(), Ok(()));
+
+        let mut tx = valid_transaction();
+        tx.witness.output_blindings[1] = [0xffu8; 32];
+        assert_eq!(
+            tx.validate(),
+            Err(ValidationError::NonCanonicalScalar {
+                field: "output blinding",
+                index: 1
+            })
+        );
+
+        let mut tx = valid_transaction();
+        tx.witness.output_amounts[0] = 61;
+        assert!(matches!(
+            tx.validate(),
+            Err(ValidationError::Balance(BalanceError::Unbalanced { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_validate_rejects_unopened_output() {
+        // An output commitment with no opening would never be checked
+        let mut tx = valid_transaction();
+        tx.witness.output_amounts.pop();
+        tx.witness.output_blindings.pop();
+        assert_eq!(
+            tx.validate(),
+            Err(ValidationError::LengthMismatch {
+                field: "output amounts",
+                expected: 2,
+                actual: 1
+            })
+        );
+
+        let mut tx = valid_transaction();
+        tx.public.output_notes.clear();
+        assert_eq!(
+            tx.validate(),
+            Err(ValidationError::LengthMismatch {
+                field: "output notes",
+                expected: 2,
+                actual: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_duplicate_key_image() {
+        let mut tx = valid_transaction();
+        let public = &mut tx.public;
+        public.input_commitments.push(public.input_commitments[0]);
+        public.key_images.push(public.key_images[0]);
+        public.rings.push(public.rings[0].clone());
+        public
+            .ring_signatures
+            .push(public.ring_signatures[0].clone());
+        tx.witness.input_amounts = vec![50, 50];
+        tx.witness.input_blindings.push(scalar(3));
+        tx.witness.secret_indices.push(0);
+
+        assert_eq!(
+            tx.validate(),
+            Err(ValidationError::DuplicateKeyImage { input: 1 })
+        );
+    }
+
+    #[test]
+    fn test_deposit_transaction() {
+        let deposit = DepositTransaction::new(
+            NATIVE_TOKEN,
+            1000,
+            CommitmentData::new([1u8; 32]),
+            StealthAddressData::new(vec![2u8; 33], [0x42u8; 20]),
+            [3u8; 32],
+        );
+        assert!(deposit.is_native());
+
+        let erc20 = DepositTransaction::new(
+            [0x77u8; 20],
+            1000,
+            CommitmentData::new([1u8; 32]),
+            StealthAddressData::new(vec![2u8; 33], [0x42u8; 20]),
+            [3u8; 32],
+        );
+        assert!(!erc20.is_native());
+
+        let debug = format!("{:?}", deposit);
+        assert!(debug.contains("1000"));
+        assert!(!debug.contains("output_blinding"));
+    }
+
+    #[cfg(feature = "hex")]
+    #[test]
+    fn test_json_hex_fields() {
+        let tx = valid_transaction();
+        let json = serde_json::to_value(&tx.public).unwrap();
+
+        let key_image = crate::serde_hex::encode(&point(2));
+        assert_eq!(json["key_images"][0], key_image.as_str());
+        assert_eq!(
+            json["stealth_addresses"][0]["stealth_address"],
+            format!("0x{}", "42".repeat(20)).as_str()
+        );
+        assert!(json["relayer"].is_null());
+
+        let decoded: PublicTransaction = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded.txid(), tx.public.txid());
+
+        // Binary encoding stays compact.
+        let bytes = bincode::serialize(&tx.public).unwrap();
+        let decoded: PublicTransaction = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(decoded.txid(), tx.public.txid());
+    }
 }
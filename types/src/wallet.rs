@@ -1,20 +1,132 @@
 use crate::stealth::EthAddress;
+use alloc::vec::Vec;
+use core::fmt;
+use parity_scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
 use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Wiped on drop. Debug output is redacted.
+#[derive(Serialize, Deserialize, Encode, Decode, TypeInfo, Zeroize, ZeroizeOnDrop)]
 pub struct WalletState {
-    pub view_secret: [u8; 32],
-    pub spend_secret: [u8; 32],
+    pub keys: WalletKeys,
     pub outputs: Vec<OwnedOutput>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl WalletState {
+    pub fn is_view_only(&self) -> bool {
+        matches!(self.keys, WalletKeys::ViewOnly(_))
+    }
+}
+
+impl fmt::Debug for WalletState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WalletState")
+            .field("keys", &self.keys)
+            .field("outputs", &self.outputs)
+            .finish()
+    }
+}
+
+/// A full wallet holds every secret. A view-only wallet holds just the
+/// viewing key: it sees incoming outputs but cannot spend or detect spends.
+#[derive(Serialize, Deserialize, Encode, Decode, TypeInfo, Zeroize)]
+pub enum WalletKeys {
+    Full {
+        #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex"))]
+        view_secret: [u8; 32],
+        #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex"))]
+        spend_secret: [u8; 32],
+        #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex"))]
+        ring_secret: [u8; 32],
+    },
+    ViewOnly(ViewingKeyData),
+}
+
+impl fmt::Debug for WalletKeys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Full { .. } => f.debug_struct("Full").finish_non_exhaustive(),
+            Self::ViewOnly(key) => f.debug_tuple("ViewOnly").field(key).finish(),
+        }
+    }
+}
+
+/// View secret plus the public halves of the spend and ring keys. Wiped on
+/// drop; Debug output leaves out the view secret. Not `Clone`, so copies of
+/// the view secret only come from [`ViewingKeyData::copy`].
+#[derive(Serialize, Deserialize, Encode, Decode, TypeInfo, Zeroize, ZeroizeOnDrop)]
+pub struct ViewingKeyData {
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex"))]
+    pub view_secret: [u8; 32],
+    /// Compressed secp256k1 spend public key (33 bytes).
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex::bytes"))]
+    pub spend_pubkey: Vec<u8>,
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex"))]
+    pub ring_pubkey: [u8; 32],
+}
+
+impl ViewingKeyData {
+    /// Copies the key, view secret included. The copy is wiped on drop too.
+    pub fn copy(&self) -> Self {
+        Self {
+            view_secret: self.view_secret,
+            spend_pubkey: self.spend_pubkey.clone(),
+            ring_pubkey: self.ring_pubkey,
+        }
+    }
+}
+
+impl fmt::Debug for ViewingKeyData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ViewingKeyData")
+            .field("spend_pubkey", &self.spend_pubkey)
+            .field("ring_pubkey", &self.ring_pubkey)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Wiped on drop. Debug output leaves out the blinding and the key offset. Not
+/// `Clone`: spend it by reference.
+#[derive(Serialize, Deserialize, Encode, Decode, TypeInfo, Zeroize, ZeroizeOnDrop)]
 pub struct OwnedOutput {
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex"))]
     pub commitment: [u8; 32],
     pub amount: u64,
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex"))]
     pub blinding: [u8; 32],
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex"))]
     pub stealth_address: EthAddress,
+    /// One-time ring key of this output.
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex"))]
+    pub ring_pubkey: [u8; 32],
+    /// Scalar added to the wallet's ring secret to get the one-time secret.
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex"))]
+    pub ring_key_offset: [u8; 32],
+    /// Key image this output reveals when spent. All zero in a view-only
+    /// wallet, which cannot compute key images.
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex"))]
+    pub key_image: [u8; 32],
+    /// Block the output was included in; `None` while it is only in the mempool.
+    pub height: Option<u64>,
     pub spent: bool,
+    /// Block the spending transaction was included in, if known.
+    pub spent_height: Option<u64>,
+}
+
+impl fmt::Debug for OwnedOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OwnedOutput")
+            .field("commitment", &self.commitment)
+            .field("amount", &self.amount)
+            .field("stealth_address", &self.stealth_address)
+            .field("ring_pubkey", &self.ring_pubkey)
+            .field("key_image", &self.key_image)
+            .field("height", &self.height)
+            .field("spent", &self.spent)
+            .field("spent_height", &self.spent_height)
+            .finish_non_exhaustive()
+    }
 }
 
 impl OwnedOutput {
@@ -25,24 +137,112 @@ impl OwnedOutput {
     pub fn is_unspent(&self) -> bool {
         !self.spent
     }
+
+    pub fn is_confirmed(&self) -> bool {
+        self.height.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Encode, Decode, TypeInfo)]
+pub enum HistoryKind {
+    Received,
+    Spent,
+}
+
+/// One balance change of a wallet, as reported by its history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Encode, Decode, TypeInfo)]
+pub struct HistoryEntry {
+    pub kind: HistoryKind,
+    pub amount: u64,
+    #[cfg_attr(feature = "hex", serde(with = "crate::serde_hex"))]
+    pub commitment: [u8; 32],
+    /// `None` while the transaction is pending.
+    pub height: Option<u64>,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloc::{format, vec};
 
-    #[test]
-    fn test_owned_output() {
-        let mut output = OwnedOutput {
+    fn output() -> OwnedOutput {
+        OwnedOutput {
             commitment: [1u8; 32],
             amount: 100,
             blinding: [2u8; 32],
             stealth_address: [0x42u8; 20],
+            ring_pubkey: [3u8; 32],
+            ring_key_offset: [4u8; 32],
+            key_image: [5u8; 32],
+            height: None,
             spent: false,
-        };
+            spent_height: None,
+        }
+    }
+
+    #[test]
+    fn test_owned_output() {
+        let mut output = output();
 
         assert!(output.is_unspent());
+        assert!(!output.is_confirmed());
         output.mark_spent();
         assert!(!output.is_unspent())
     }
+
+    fn full_keys() -> WalletKeys {
+        WalletKeys::Full {
+            view_secret: [0xa1u8; 32],
+            spend_secret: [0xa2u8; 32],
+            ring_secret: [0xa3u8; 32],
+        }
+    }
+
+    #[test]
+    fn test_debug_redacts_secrets() {
+        let state = WalletState {
+            keys: full_keys(),
+            outputs: vec![output()],
+        };
+        let debug = format!("{:?}", state);
+
+        for secret in [161, 162, 163, 2, 4] {
+            assert!(!debug.contains(&format!("[{}, {}", secret, secret)));
+        }
+        assert!(debug.contains("amount: 100"));
+
+        let key = ViewingKeyData {
+            view_secret: [0xa1u8; 32],
+            spend_pubkey: vec![0xb2u8; 33],
+            ring_pubkey: [0xb3u8; 32],
+        };
+        let view_only = WalletState {
+            keys: WalletKeys::ViewOnly(key.copy()),
+            outputs: Vec::new(),
+        };
+        let debug = format!("{:?}", view_only);
+        assert!(view_only.is_view_only());
+        assert!(!debug.contains("161, 161"));
+        assert!(debug.contains("178, 178"));
+        let WalletKeys::ViewOnly(copy) = &view_only.keys else {
+            panic!("keys changed mode");
+        };
+        assert_eq!(copy.view_secret, key.view_secret);
+        assert_eq!(copy.spend_pubkey, key.spend_pubkey);
+    }
+
+    #[test]
+    fn test_zeroize() {
+        let mut state = WalletState {
+            keys: full_keys(),
+            outputs: vec![output()],
+        };
+        state.zeroize();
+
+        let WalletKeys::Full { spend_secret, .. } = state.keys else {
+            panic!("keys changed mode");
+        };
+        assert_eq!(spend_secret, [0u8; 32]);
+        assert!(state.outputs.is_empty());
+    }
 }
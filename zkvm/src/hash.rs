@@ -0,0 +1,40 @@
+// Hashes the guest must compute exactly like cryptography-crypto. Kept in
+// their own file so the host tests can compile it and compare the two.
+
+use cryptography_types::domain::{HASH_TO_POINT_DOMAIN, PEDERSEN_H_DOMAIN, RING_SIG_DOMAIN};
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint, scalar::Scalar,
+};
+use sha2::{Digest, Sha512};
+
+pub fn get_h_generator() -> RistrettoPoint {
+    let g = RISTRETTO_BASEPOINT_POINT;
+    let g_bytes = g.compress().to_bytes();
+
+    let mut hasher = Sha512::new();
+    hasher.update(PEDERSEN_H_DOMAIN);
+    hasher.update(g_bytes);
+    let hash = hasher.finalize();
+
+    RistrettoPoint::from_uniform_bytes(&hash.into())
+}
+
+pub fn hash_to_point(point: &RistrettoPoint) -> RistrettoPoint {
+    let mut hasher = Sha512::new();
+    hasher.update(HASH_TO_POINT_DOMAIN);
+    hasher.update(point.compress().as_bytes());
+    let hash = hasher.finalize();
+
+    RistrettoPoint::from_uniform_bytes(&hash.into())
+}
+
+pub fn hash_challenge(message: &[u8], l: &RistrettoPoint, r: &RistrettoPoint) -> Scalar {
+    let mut hasher = Sha512::new();
+    hasher.update(RING_SIG_DOMAIN);
+    hasher.update(message);
+    hasher.update(l.compress().as_bytes());
+    hasher.update(r.compress().as_bytes());
+
+    let hash = hasher.finalize();
+    Scalar::from_bytes_mod_order_wide(&hash.into())
+}
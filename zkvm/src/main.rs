@@ -1,12 +1,23 @@
-// This program runs inside the zkVM and verifies:
-// 1. Ring signature is valid (sender anonymity)
-// 2. Commitments balance: sum(inputs) = sum(output)
-// 3. Key image is correctly computes (prevents double-spend)
+// This program runs inside the zkVM and verifies transfers:
+// 1. Ring signatures over the txid are valid (sender anonymity)
+// 2. Commitments balance: sum(inputs) = sum(output) + sum(withdrawals) + fee
+// 3. Key images are distinct within the transaction (prevents double-spend)
+//
+// and deposits:
+// 1. The output commitment opens to the publicly deposited amount
 
 #![no_main]
 sp1_zkvm::entrypoint!(main);
 
-use cryptography_types::{proof::PublicInputs, transaction::PrivateTransaction};
+mod hash;
+
+use cryptography_types::{
+    proof::{DepositPublicInputs, PublicInputs, PublicValues},
+    transaction::{
+        DepositTransaction, PrivateTransaction, PublicTransaction, TransactionKind,
+        TransactionWitness,
+    },
+};
 
 use curve25519_dalek::{
     constants::RISTRETTO_BASEPOINT_POINT,
@@ -14,54 +25,69 @@ use curve25519_dalek::{
     scalar::Scalar,
 };
 
-use sha2::{Digest, Sha512};
+use hash::{get_h_generator, hash_challenge, hash_to_point};
 
 pub fn main() {
-    let tx: PrivateTransaction = sp1_zkvm::io::read();
-
-    // Step 1: Verify Ring Signature
-    let key_image = parse_ristretto_point(&tx.key_image).expect("Invalid key image");
+    let kind: TransactionKind = sp1_zkvm::io::read();
 
-    let mut ring: Vec<RistrettoPoint> = Vec::new();
-    for member_bytes in &tx.ring {
-        let point = parse_ristretto_point(member_bytes).expect("Invalid ring member");
-        ring.push(point);
-    }
+    let public_values = match kind {
+        TransactionKind::Transfer => {
+            let tx: PublicTransaction = sp1_zkvm::io::read();
+            let witness: TransactionWitness = sp1_zkvm::io::read();
+            PublicValues::Transfer(verify_transfer(tx, witness))
+        }
+        TransactionKind::Deposit => PublicValues::Deposit(verify_deposit(sp1_zkvm::io::read())),
+    };
 
-    let mut c_values: Vec<Scalar> = Vec::new();
-    let mut r_values: Vec<Scalar> = Vec::new();
+    sp1_zkvm::io::commit(&public_values);
+}
 
-    for c_bytes in &tx.ring_signature.c {
-        let scalar = parse_scalar(c_bytes).expect("Invalid challenge");
-        c_values.push(scalar)
-    }
+fn verify_transfer(tx: PublicTransaction, witness: TransactionWitness) -> PublicInputs {
+    // The host validates too, but a prover can skip that. Without it, an output
+    // commitment with no opening in the witness would never be checked. This
+    // covers lengths, ring shapes, duplicate key images and the balance; points
+    // and scalars are decoded once below, and decoding rejects bad encodings.
+    let (tx, witness) = {
+        let private = PrivateTransaction::new(tx, witness);
+        if let Err(e) = private.validate_structure() {
+            panic!("Invalid transaction: {}", e);
+        }
+        private.into_parts()
+    };
 
-    for r_bytes in &tx.ring_signature.r {
-        let scalar = parse_scalar(r_bytes).expect("Invalid response");
-        r_values.push(scalar);
+    // Step 1: Verify one Ring Signature per input. Every ring signature signs
+    // the txid, binding it to the whole transaction
+    let txid = tx.txid().expect("Validated above");
+
+    for (i, (ring_bytes, signature)) in tx.rings.iter().zip(&tx.ring_signatures).enumerate() {
+        let key_image = parse_ristretto_point(&tx.key_images[i]).expect("Invalid key image");
+
+        let ring: Vec<RistrettoPoint> = ring_bytes
+            .iter()
+            .map(|member| parse_ristretto_point(member).expect("Invalid ring member"))
+            .collect();
+        let c_values: Vec<Scalar> = signature
+            .c
+            .iter()
+            .map(|c| parse_scalar(c).expect("Invalid challenge"))
+            .collect();
+        let r_values: Vec<Scalar> = signature
+            .r
+            .iter()
+            .map(|r| parse_scalar(r).expect("Invalid response"))
+            .collect();
+
+        let ring_valid = verify_ring_signature(&txid, &key_image, &ring, &c_values, &r_values);
+        assert!(ring_valid, "Ring signature {} verification failed", i);
     }
 
-    let message = b"PRIVATE_PAYMENT_TX";
-    let ring_valid = verify_ring_signature(message, &key_image, &ring, &c_values, &r_values);
-
-    assert!(ring_valid, "Ring signature verification failed");
-
-    // Step 2 Verify Commitment Balance
-    let input_sum: u64 = tx.input_amounts.iter().sum();
-    let output_sum: u64 = tx.output_amounts.iter().sum();
-
-    assert_eq!(
-        input_sum, output_sum,
-        "Transaction not balanced: inputs={}, outputs={}",
-        input_sum, output_sum
-    );
-
-    // Verify Input
-    for (i, amount) in tx.input_amounts.iter().enumerate() {
-        let blinding = parse_scalar(&tx.input_blindings[i]).expect("Invalid input blinding");
-        let computed_commitment = pedersen_commitment(*amount, &blinding);
-        let claimed_commitment = parse_ristretto_point(&tx.input_commitments[i].commitment)
-            .expect("Invalid input commitment");
+    // Step 2: Verify every commitment opens to its witness amount; the amounts
+    // themselves were checked to balance above
+    for (i, commitment) in tx.input_commitments.iter().enumerate() {
+        let blinding = parse_scalar(&witness.input_blindings[i]).expect("Invalid input blinding");
+        let computed_commitment = pedersen_commitment(witness.input_amounts[i], &blinding);
+        let claimed_commitment =
+            parse_ristretto_point(&commitment.commitment).expect("Invalid input commitment");
 
         assert_eq!(
             computed_commitment, claimed_commitment,
@@ -70,13 +96,12 @@ pub fn main() {
         );
     }
 
-    // Verify Output
-    for (i, amount) in tx.output_amounts.iter().enumerate() {
-        let blinding = parse_scalar(&tx.output_blindings[i]).expect("Invalid output blinding");
+    for (i, commitment) in tx.output_commitments.iter().enumerate() {
+        let blinding = parse_scalar(&witness.output_blindings[i]).expect("Invalid output blinding");
+        let computed_commitment = pedersen_commitment(witness.output_amounts[i], &blinding);
+        let claimed_commitment =
+            parse_ristretto_point(&commitment.commitment).expect("Invalid output commitment");
 
-        let computed_commitment = pedersen_commitment(*amount, &blinding);
-        let claimed_commitment = parse_ristretto_point(&tx.output_commitments[i].commitment)
-            .expect("Invalid output commitment");
         assert_eq!(
             computed_commitment, claimed_commitment,
             "Output commitment {} does not match",
@@ -84,27 +109,48 @@ pub fn main() {
         );
     }
 
-    // Step 3 Verify Key Image
-    let secret_index = tx.secret_index;
-    assert!(
-        secret_index < ring.len(),
-        "Invalid secret index: {} >= {}",
-        secret_index,
-        ring.len()
-    );
-
-    // Step 4 Commit Public Inputs
+    // Recipients later spend with these keys, so they must be points too
+    for note in &tx.output_notes {
+        parse_ristretto_point(&note.ring_pubkey).expect("Invalid output note ring key");
+    }
 
-    let public_inputs = PublicInputs {
+    // Step 3 Public Inputs
+    PublicInputs {
+        txid,
         input_commitments: tx.input_commitments.iter().map(|c| c.commitment).collect(),
         output_commitments: tx.output_commitments.iter().map(|c| c.commitment).collect(),
-        key_image: tx.key_image,
-        ring: tx.ring.clone(),
-    };
+        key_images: tx.key_images.clone(),
+        rings: tx.rings.clone(),
+        withdrawals: tx.withdrawals.clone(),
+        fee: tx.fee,
+        relayer: tx.relayer,
+    }
+}
 
-    sp1_zkvm::io::commit(&public_inputs);
+fn verify_deposit(deposit: DepositTransaction) -> DepositPublicInputs {
+    // Decoding rejects exactly what DepositTransaction::validate does
+    let blinding = parse_scalar(&deposit.output_blinding).expect("Invalid deposit blinding");
+
+    let computed_commitment = pedersen_commitment(deposit.amount, &blinding);
+    let claimed_commitment = parse_ristretto_point(&deposit.output_commitment.commitment)
+        .expect("Invalid deposit commitment");
+
+    assert_eq!(
+        computed_commitment, claimed_commitment,
+        "Deposit commitment does not open to amount {}",
+        deposit.amount
+    );
+
+    DepositPublicInputs {
+        token: deposit.token,
+        amount: deposit.amount,
+        output_commitment: deposit.output_commitment.commitment,
+        stealth_address: deposit.stealth_address.clone(),
+    }
 }
 
+/// Lengths were checked by `validate_structure`: `c` and `r` match the ring,
+/// which is not empty.
 fn verify_ring_signature(
     message: &[u8],
     key_image: &RistrettoPoint,
@@ -114,14 +160,6 @@ fn verify_ring_signature(
 ) -> bool {
     let n = ring.len();
 
-    if c_values.len() != n || r_values.len() != n {
-        return false;
-    }
-
-    if n == 0 {
-        return false;
-    }
-
     for i in 0..n {
         let next_i = (i + 1) % n;
 
@@ -146,42 +184,11 @@ fn pedersen_commitment(amount: u64, blinding: &Scalar) -> RistrettoPoint {
     Scalar::from(amount) * g + blinding * h
 }
 
-fn get_h_generator() -> RistrettoPoint {
-    let g = RISTRETTO_BASEPOINT_POINT;
-    let g_bytes = g.compress().to_bytes();
-
-    let mut hasher = Sha512::new();
-    hasher.update(b"Pedersen_H_GENERATOR_V2");
-    hasher.update(&g_bytes);
-    let hash = hasher.finalize();
-
-    RistrettoPoint::from_uniform_bytes(&hash.into())
-}
-
-fn hash_to_point(point: &RistrettoPoint) -> RistrettoPoint {
-    let mut hasher = Sha512::new();
-    hasher.update(b"HASH_TO_POINTS_V1");
-    hasher.update(point.compress().as_bytes());
-    let hash = hasher.finalize();
-
-    RistrettoPoint::from_uniform_bytes(&hash.into())
-}
-
-fn hash_challenge(message: &[u8], l: &RistrettoPoint, r: &RistrettoPoint) -> Scalar {
-    let mut hasher = Sha512::new();
-    hasher.update(b"RING_SIG_V1");
-    hasher.update(message);
-    hasher.update(l.compress().as_bytes());
-    hasher.update(r.compress().as_bytes());
-
-    let hash = hasher.finalize();
-    Scalar::from_bytes_mod_order_wide(&hash.into())
-}
-
 fn parse_ristretto_point(bytes: &[u8; 32]) -> Option<RistrettoPoint> {
     CompressedRistretto(*bytes).decompress()
 }
 
+/// Rejects non-canonical encodings, so each scalar has exactly one.
 fn parse_scalar(bytes: &[u8; 32]) -> Option<Scalar> {
-    Some(Scalar::from_bytes_mod_order(*bytes))
+    Scalar::from_canonical_bytes(*bytes).into()
 }